@@ -0,0 +1,84 @@
+//! Compares the Vec-backed `Stack` with `SegmentedStack` on very deep stacks.
+//!
+//! Run with `cargo bench --bench segmented`. Besides the total time, it
+//! reports the slowest single push, which is where the Vec-backed stack pays
+//! for copying every element into a larger buffer.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rust::{OverflowPolicy, SegmentedStack, Stack};
+
+const DEPTH: usize = 10_000_000;
+const ROUNDS: usize = 5;
+
+struct Timings {
+    push: Duration,
+    slowest_push: Duration,
+    pop: Duration,
+}
+
+fn measure<S>(
+    mut stack: S,
+    push: impl Fn(&mut S, u64),
+    pop: impl Fn(&mut S) -> Option<u64>,
+) -> Timings {
+    let mut slowest_push = Duration::ZERO;
+    let start = Instant::now();
+    for value in 0..DEPTH as u64 {
+        let before = Instant::now();
+        push(&mut stack, black_box(value));
+        slowest_push = slowest_push.max(before.elapsed());
+    }
+    let push_time = start.elapsed();
+
+    let start = Instant::now();
+    while let Some(value) = pop(&mut stack) {
+        black_box(value);
+    }
+
+    Timings {
+        push: push_time,
+        slowest_push,
+        pop: start.elapsed(),
+    }
+}
+
+fn report(name: &str, timings: &[Timings]) {
+    let best =
+        |field: fn(&Timings) -> Duration| timings.iter().map(field).min().unwrap_or_default();
+
+    println!(
+        "{:<16} push {:>10.2?}  slowest push {:>10.2?}  pop {:>10.2?}",
+        name,
+        best(|t| t.push),
+        best(|t| t.slowest_push),
+        best(|t| t.pop)
+    );
+}
+
+fn main() {
+    println!("{} elements, best of {} rounds", DEPTH, ROUNDS);
+
+    let vec_backed: Vec<Timings> = (0..ROUNDS)
+        .map(|_| {
+            measure(
+                Stack::with_policy(1, OverflowPolicy::Grow),
+                |stack, value| stack.push(value).expect("a growing stack never overflows"),
+                |stack| stack.pop().ok(),
+            )
+        })
+        .collect();
+    report("Stack (Vec)", &vec_backed);
+
+    let segmented: Vec<Timings> = (0..ROUNDS)
+        .map(|_| {
+            measure(
+                SegmentedStack::new(),
+                |stack, value| stack.push(value),
+                |stack| stack.pop().ok(),
+            )
+        })
+        .collect();
+    report("SegmentedStack", &segmented);
+}
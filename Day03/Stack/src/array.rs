@@ -0,0 +1,143 @@
+use core::fmt;
+use core::iter::Rev;
+use core::mem::MaybeUninit;
+use core::{ptr, slice};
+
+use crate::error::StackError;
+
+/// A stack of at most `N` elements stored inline, without allocating.
+///
+/// It offers the same core operations and errors as the heap-backed
+/// `Stack`, but its capacity is fixed at compile time. Only the first `len`
+/// slots of `elements` are initialized.
+pub struct ArrayStack<T, const N: usize> {
+    elements: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayStack<T, N> {
+    pub const fn new() -> Self {
+        ArrayStack {
+            elements: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    /// Pushes `element` on top of the stack.
+    ///
+    /// Fails with [`StackError::Overflow`] when the stack is full.
+    pub fn push(&mut self, element: T) -> Result<(), StackError> {
+        if self.len == N {
+            return Err(StackError::Overflow { capacity: N });
+        }
+
+        self.elements[self.len].write(element);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the top element.
+    ///
+    /// Fails with [`StackError::Underflow`] when the stack is empty.
+    pub fn pop(&mut self) -> Result<T, StackError> {
+        if self.len == 0 {
+            return Err(StackError::Underflow);
+        }
+
+        self.len -= 1;
+        // SAFETY: the slot was below `len`, so it is initialized, and it is
+        // now outside `len`, so it will not be read or dropped again.
+        Ok(unsafe { self.elements[self.len].assume_init_read() })
+    }
+
+    /// Returns a reference to the top element without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().last()
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        let initialized: *mut [T] = self.as_mut_slice();
+        // Forget the elements first, so a panicking `drop` cannot lead to
+        // them being dropped twice.
+        self.len = 0;
+        // SAFETY: the slice covered exactly the initialized elements.
+        unsafe { ptr::drop_in_place(initialized) };
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the elements from bottom to top.
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` slots are initialized, and `MaybeUninit<T>`
+        // has the same layout as `T`.
+        unsafe { slice::from_raw_parts(self.elements.as_ptr().cast::<T>(), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: as in `as_slice`.
+        unsafe { slice::from_raw_parts_mut(self.elements.as_mut_ptr().cast::<T>(), self.len) }
+    }
+
+    /// Iterates over the elements from top to bottom.
+    pub fn iter(&self) -> Rev<slice::Iter<'_, T>> {
+        self.as_slice().iter().rev()
+    }
+
+    /// Iterates over the elements from bottom to top.
+    pub fn iter_bottom_up(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayStack<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for ArrayStack<T, N> {
+    fn default() -> Self {
+        ArrayStack::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayStack<T, N> {
+    fn clone(&self) -> Self {
+        let mut clone = ArrayStack::new();
+        for element in self.as_slice() {
+            // Cannot fail: the clone has the same capacity.
+            let _ = clone.push(element.clone());
+        }
+        clone
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayStack<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayStack<T, N> {
+    type Item = &'a T;
+    type IntoIter = Rev<slice::Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::error::StackError;
+use crate::stack::Stack;
+
+struct State<T> {
+    stack: Stack<T>,
+    closed: bool,
+}
+
+/// A bounded stack shared between threads, usable as a LIFO work channel.
+///
+/// `push` waits while the stack is full and `pop` waits while it is empty.
+/// After [`BlockingStack::close`], pushes fail with [`StackError::Closed`]
+/// and pops drain what is left before failing the same way.
+pub struct BlockingStack<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl<T> BlockingStack<T> {
+    pub fn new(capacity: usize) -> Self {
+        BlockingStack {
+            state: Mutex::new(State {
+                stack: Stack::with_capacity(capacity),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    /// Pushes `value`, waiting for room while the stack is full.
+    ///
+    /// Fails with [`StackError::Closed`], dropping `value`, once the stack is
+    /// closed, including while waiting. A zero-capacity stack can never take
+    /// an element and fails with [`StackError::Overflow`] instead of waiting.
+    pub fn push(&self, value: T) -> Result<(), StackError> {
+        let mut state = self.lock();
+        if state.stack.capacity() == 0 && !state.closed {
+            return Err(StackError::Overflow { capacity: 0 });
+        }
+
+        while state.stack.is_full() && !state.closed {
+            state = self
+                .not_full
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        self.push_locked(&mut state, value)
+    }
+
+    /// Pushes `value` without waiting, failing with [`StackError::Overflow`]
+    /// if the stack is full.
+    pub fn try_push(&self, value: T) -> Result<(), StackError> {
+        let mut state = self.lock();
+        self.push_locked(&mut state, value)
+    }
+
+    /// Pops the top element, waiting while the stack is empty.
+    ///
+    /// Fails with [`StackError::Closed`] once the stack is closed and empty.
+    pub fn pop(&self) -> Result<T, StackError> {
+        let mut state = self.lock();
+        while state.stack.is_empty() && !state.closed {
+            state = self
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        self.pop_locked(&mut state)
+    }
+
+    /// Pops the top element without waiting, failing with
+    /// [`StackError::Underflow`] if the stack is empty.
+    pub fn try_pop(&self) -> Result<T, StackError> {
+        let mut state = self.lock();
+        self.pop_locked(&mut state)
+    }
+
+    /// Pops the top element, waiting at most `timeout` for one to arrive.
+    ///
+    /// Fails with [`StackError::TimedOut`] if the stack is still empty when
+    /// the timeout elapses. A timeout too long to have a deadline waits
+    /// like [`BlockingStack::pop`].
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, StackError> {
+        let deadline = match Instant::now().checked_add(timeout) {
+            Some(deadline) => deadline,
+            None => return self.pop(),
+        };
+        let mut state = self.lock();
+
+        while state.stack.is_empty() && !state.closed {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(StackError::TimedOut);
+            }
+
+            state = self
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+
+        self.pop_locked(&mut state)
+    }
+
+    /// Closes the stack and wakes every waiting thread.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().stack.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lock().stack.capacity()
+    }
+
+    /// Locks the state. A panic while holding the lock cannot leave the
+    /// stack half-updated, so a poisoned lock is simply taken over.
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push_locked(&self, state: &mut State<T>, value: T) -> Result<(), StackError> {
+        if state.closed {
+            return Err(StackError::Closed);
+        }
+
+        state.stack.push(value)?;
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn pop_locked(&self, state: &mut State<T>) -> Result<T, StackError> {
+        match state.stack.pop() {
+            Ok(value) => {
+                self.not_full.notify_one();
+                Ok(value)
+            }
+            Err(_) if state.closed => Err(StackError::Closed),
+            Err(err) => Err(err),
+        }
+    }
+}
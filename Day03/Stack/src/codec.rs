@@ -0,0 +1,128 @@
+use std::convert::{TryFrom, TryInto};
+
+/// A value with a compact binary form, used to write elements to files.
+///
+/// Numbers are stored little-endian, `char` as its `u32` code point and
+/// `String` as a little-endian `u32` byte length followed by its UTF-8 bytes.
+pub trait Codec: Sized {
+    /// The Rust name of the type, such as `i32`.
+    const TYPE_NAME: &'static str;
+    /// A number identifying the type in binary files; never reuse one.
+    const TYPE_TAG: u8;
+
+    /// Appends the encoded value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from the front of `input` and advances past it.
+    ///
+    /// Returns `None` if `input` is too short or does not hold a valid value.
+    fn decode(input: &mut &[u8]) -> Option<Self>;
+}
+
+/// Returns the [`Codec::TYPE_NAME`] of the built-in type with `tag`.
+pub fn type_name(tag: u8) -> Option<&'static str> {
+    let built_in = [
+        (i8::TYPE_TAG, i8::TYPE_NAME),
+        (i16::TYPE_TAG, i16::TYPE_NAME),
+        (i32::TYPE_TAG, i32::TYPE_NAME),
+        (i64::TYPE_TAG, i64::TYPE_NAME),
+        (i128::TYPE_TAG, i128::TYPE_NAME),
+        (u8::TYPE_TAG, u8::TYPE_NAME),
+        (u16::TYPE_TAG, u16::TYPE_NAME),
+        (u32::TYPE_TAG, u32::TYPE_NAME),
+        (u64::TYPE_TAG, u64::TYPE_NAME),
+        (u128::TYPE_TAG, u128::TYPE_NAME),
+        (f32::TYPE_TAG, f32::TYPE_NAME),
+        (f64::TYPE_TAG, f64::TYPE_NAME),
+        (bool::TYPE_TAG, bool::TYPE_NAME),
+        (char::TYPE_TAG, char::TYPE_NAME),
+        (String::TYPE_TAG, String::TYPE_NAME),
+    ];
+
+    built_in
+        .iter()
+        .find(|&&(candidate, _)| candidate == tag)
+        .map(|&(_, name)| name)
+}
+
+/// Splits off the first `len` bytes of `input`.
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len {
+        return None;
+    }
+
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Some(head)
+}
+
+macro_rules! number_codec {
+    ($($number:ident = $tag:literal)*) => {$(
+        impl Codec for $number {
+            const TYPE_NAME: &'static str = stringify!($number);
+            const TYPE_TAG: u8 = $tag;
+
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode(input: &mut &[u8]) -> Option<Self> {
+                let bytes = take(input, std::mem::size_of::<$number>())?;
+                Some($number::from_le_bytes(bytes.try_into().ok()?))
+            }
+        }
+    )*};
+}
+
+number_codec! {
+    i8 = 1 i16 = 2 i32 = 3 i64 = 4 i128 = 5
+    u8 = 6 u16 = 7 u32 = 8 u64 = 9 u128 = 10
+    f32 = 11 f64 = 12
+}
+
+impl Codec for bool {
+    const TYPE_NAME: &'static str = "bool";
+    const TYPE_TAG: u8 = 13;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        match u8::decode(input)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for char {
+    const TYPE_NAME: &'static str = "char";
+    const TYPE_TAG: u8 = 14;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        u32::from(*self).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        char::from_u32(u32::decode(input)?)
+    }
+}
+
+impl Codec for String {
+    const TYPE_NAME: &'static str = "String";
+    const TYPE_TAG: u8 = 15;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        let len = u32::try_from(self.len()).expect("strings longer than 4 GiB cannot be encoded");
+        len.encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        let len = u32::decode(input)? as usize;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
@@ -0,0 +1,195 @@
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use crate::error::StackError;
+
+struct Node<T> {
+    value: ManuallyDrop<T>,
+    /// The node below this one; never changes once the node is published.
+    next: *mut Node<T>,
+    /// Links nodes waiting to be freed; only touched by the thread holding them.
+    pending_next: *mut Node<T>,
+}
+
+/// A lock-free stack (Treiber stack) that many threads can push to and pop
+/// from through a shared reference.
+///
+/// Popped nodes are not freed while another thread is inside `pop`, since
+/// that thread may still be reading them. They are parked on a pending list
+/// and freed by whichever thread is last to leave `pop`. Because a node is
+/// never freed, and so never reused, while a pop might still compare against
+/// it, the compare-and-swap on `head` is also safe from the ABA problem.
+pub struct ConcurrentStack<T> {
+    head: AtomicPtr<Node<T>>,
+    len: AtomicUsize,
+    threads_in_pop: AtomicUsize,
+    pending: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for ConcurrentStack<T> {}
+unsafe impl<T: Send> Sync for ConcurrentStack<T> {}
+
+impl<T> ConcurrentStack<T> {
+    pub fn new() -> Self {
+        ConcurrentStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            threads_in_pop: AtomicUsize::new(0),
+            pending: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            value: ManuallyDrop::new(value),
+            next: ptr::null_mut(),
+            pending_next: ptr::null_mut(),
+        }));
+
+        // Counted before the node is published, so the pop that takes it,
+        // which synchronizes with the `Release` below, always decrements
+        // after this and `len` never wraps below zero.
+        self.len.fetch_add(1, Ordering::Relaxed);
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: `node` is not yet published, so this thread owns it.
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Removes and returns the top element.
+    ///
+    /// Fails with [`StackError::Underflow`] when the stack is empty.
+    pub fn pop(&self) -> Result<T, StackError> {
+        self.threads_in_pop.fetch_add(1, Ordering::SeqCst);
+
+        let mut head = self.head.load(Ordering::Acquire);
+        while !head.is_null() {
+            // SAFETY: `head` cannot be freed while this thread is counted in
+            // `threads_in_pop`.
+            let next = unsafe { (*head).next };
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        if head.is_null() {
+            self.threads_in_pop.fetch_sub(1, Ordering::SeqCst);
+            return Err(StackError::Underflow);
+        }
+
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        // SAFETY: unlinking `head` gave this thread sole ownership of its
+        // value; the node itself is only freed through `retire`, which never
+        // drops the value again.
+        let value = unsafe { ptr::read(&*(*head).value) };
+        self.retire(head);
+        Ok(value)
+    }
+
+    /// Returns `true` if the stack was empty at the moment it was checked.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// Returns the number of elements, which may already be stale when other
+    /// threads are pushing or popping.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Frees `node`, or parks it until no other thread is inside `pop`.
+    fn retire(&self, node: *mut Node<T>) {
+        if self.threads_in_pop.load(Ordering::SeqCst) == 1 {
+            let pending = self.pending.swap(ptr::null_mut(), Ordering::SeqCst);
+
+            if self.threads_in_pop.fetch_sub(1, Ordering::SeqCst) == 1 {
+                // SAFETY: no other thread is in `pop`, so nobody can still
+                // hold a pointer to any pending node.
+                unsafe { free_pending(pending) };
+            } else if !pending.is_null() {
+                self.park(pending);
+            }
+
+            // SAFETY: `node` was unlinked before this thread was found alone
+            // in `pop`, so no thread that arrived later can have reached it.
+            unsafe { drop(Box::from_raw(node)) };
+        } else {
+            // SAFETY: this thread owns `node`; parking only links it.
+            unsafe { (*node).pending_next = ptr::null_mut() };
+            self.park(node);
+            self.threads_in_pop.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Pushes a chain of nodes linked through `pending_next` onto the pending list.
+    fn park(&self, first: *mut Node<T>) {
+        let mut last = first;
+        // SAFETY: the chain is owned by this thread until it is published below.
+        unsafe {
+            while !(*last).pending_next.is_null() {
+                last = (*last).pending_next;
+            }
+        }
+
+        let mut pending = self.pending.load(Ordering::SeqCst);
+        loop {
+            // SAFETY: as above, `last` is still owned by this thread.
+            unsafe { (*last).pending_next = pending };
+            match self.pending.compare_exchange_weak(
+                pending,
+                first,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => break,
+                Err(current) => pending = current,
+            }
+        }
+    }
+}
+
+/// Frees a chain of popped nodes whose values have already been moved out.
+unsafe fn free_pending<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        let next = (*node).pending_next;
+        drop(Box::from_raw(node));
+        node = next;
+    }
+}
+
+impl<T> Default for ConcurrentStack<T> {
+    fn default() -> Self {
+        ConcurrentStack::new()
+    }
+}
+
+impl<T> Drop for ConcurrentStack<T> {
+    fn drop(&mut self) {
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            // SAFETY: `&mut self` means no other thread can touch the stack,
+            // and every node still linked from `head` owns its value.
+            unsafe {
+                let mut boxed = Box::from_raw(node);
+                ManuallyDrop::drop(&mut boxed.value);
+                node = boxed.next;
+            }
+        }
+
+        // SAFETY: as above; pending nodes no longer own their values.
+        unsafe { free_pending(*self.pending.get_mut()) };
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt::{self, Display};
+use std::num::IntErrorKind;
+use std::ops::Add;
+use std::str::FromStr;
+
+use crate::error::InputErrorKind;
+
+/// A base that integer elements can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Radix {
+    Binary,
+    Octal,
+    #[default]
+    Decimal,
+    Hexadecimal,
+}
+
+impl Radix {
+    pub fn value(self) -> u32 {
+        match self {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        }
+    }
+}
+
+/// A running total of numeric elements.
+///
+/// Integers are added exactly in the widest integer type of their sign, and
+/// only fall back to a [`Total::Float`] if even that overflows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Total {
+    Signed(i128),
+    Unsigned(u128),
+    Float(f64),
+}
+
+impl Total {
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Total::Signed(total) => total as f64,
+            Total::Unsigned(total) => total as f64,
+            Total::Float(total) => total,
+        }
+    }
+}
+
+impl Add for Total {
+    type Output = Total;
+
+    fn add(self, other: Total) -> Total {
+        let exact = match (self, other) {
+            (Total::Signed(a), Total::Signed(b)) => a.checked_add(b).map(Total::Signed),
+            (Total::Unsigned(a), Total::Unsigned(b)) => a.checked_add(b).map(Total::Unsigned),
+            _ => None,
+        };
+        exact.unwrap_or_else(|| Total::Float(self.to_f64() + other.to_f64()))
+    }
+}
+
+impl Display for Total {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Total::Signed(total) => total.fmt(f),
+            Total::Unsigned(total) => total.fmt(f),
+            Total::Float(total) => total.fmt(f),
+        }
+    }
+}
+
+/// A value that can be read from and written to a line of text.
+///
+/// The default methods defer to [`FromStr`] and [`Display`]; the integer
+/// types override them to understand every Rust integer literal form.
+pub trait Element: FromStr + Display {
+    /// Parses a single whitespace-free token.
+    fn parse_token(token: &str) -> Result<Self, InputErrorKind> {
+        token.parse().map_err(|_| InputErrorKind::Malformed)
+    }
+
+    /// Formats the element in `radix`, or returns `None` if the type has no
+    /// representation in that base.
+    fn to_radix_string(&self, radix: Radix) -> Option<String> {
+        match radix {
+            Radix::Decimal => Some(self.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Returns the element as the start of a running total, or `None` if the
+    /// type is not numeric.
+    fn to_total(&self) -> Option<Total> {
+        None
+    }
+}
+
+/// Splits an integer literal such as `-0xFF_u8` into its sign, radix and
+/// digits, with underscores removed.
+///
+/// A type suffix is only accepted if it names `type_name`.
+fn split_int_literal(token: &str, type_name: &str) -> Result<(bool, u32, String), InputErrorKind> {
+    let (negative, unsigned) = match token.as_bytes().first() {
+        Some(b'-') => (true, &token[1..]),
+        Some(b'+') => (false, &token[1..]),
+        _ => (false, token),
+    };
+
+    let (radix, body) = if let Some(body) = unsigned.strip_prefix("0x") {
+        (16, body)
+    } else if let Some(body) = unsigned.strip_prefix("0o") {
+        (8, body)
+    } else if let Some(body) = unsigned.strip_prefix("0b") {
+        (2, body)
+    } else {
+        (10, unsigned)
+    };
+
+    // Hexadecimal digits include `b`, so only look for a suffix after the digits.
+    let suffix_start = body
+        .find(|c: char| c != '_' && !c.is_digit(radix))
+        .unwrap_or(body.len());
+    let (body, suffix) = body.split_at(suffix_start);
+    if !suffix.is_empty() && suffix != type_name {
+        return Err(InputErrorKind::Malformed);
+    }
+
+    if radix == 10 && body.starts_with('_') {
+        return Err(InputErrorKind::Malformed);
+    }
+
+    let digits: String = body.chars().filter(|&c| c != '_').collect();
+    if digits.is_empty() {
+        return Err(InputErrorKind::Malformed);
+    }
+
+    Ok((negative, radix, digits))
+}
+
+fn int_error(err: std::num::ParseIntError) -> InputErrorKind {
+    match err.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => InputErrorKind::OutOfRange,
+        _ => InputErrorKind::Malformed,
+    }
+}
+
+macro_rules! int_element {
+    ($magnitude:ident, $total:ident($wide:ty) => $($int:ident)*) => {$(
+        impl Element for $int {
+            fn parse_token(token: &str) -> Result<Self, InputErrorKind> {
+                let (negative, radix, digits) = split_int_literal(token, stringify!($int))?;
+                let signed = if negative { format!("-{}", digits) } else { digits };
+
+                $int::from_str_radix(&signed, radix).map_err(int_error)
+            }
+
+            fn to_radix_string(&self, radix: Radix) -> Option<String> {
+                let (sign, magnitude) = $magnitude!(*self);
+
+                Some(match radix {
+                    Radix::Binary => format!("{}{:#b}", sign, magnitude),
+                    Radix::Octal => format!("{}{:#o}", sign, magnitude),
+                    Radix::Decimal => self.to_string(),
+                    Radix::Hexadecimal => format!("{}{:#x}", sign, magnitude),
+                })
+            }
+
+            fn to_total(&self) -> Option<Total> {
+                Some(Total::$total(*self as $wide))
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_magnitude {
+    ($value:expr) => {
+        (if $value < 0 { "-" } else { "" }, $value.unsigned_abs())
+    };
+}
+
+macro_rules! unsigned_magnitude {
+    ($value:expr) => {
+        ("", $value)
+    };
+}
+
+int_element!(signed_magnitude, Signed(i128) => i8 i16 i32 i64 i128 isize);
+int_element!(unsigned_magnitude, Unsigned(u128) => u8 u16 u32 u64 u128 usize);
+
+impl Element for f32 {
+    fn to_total(&self) -> Option<Total> {
+        Some(Total::Float(f64::from(*self)))
+    }
+}
+
+impl Element for f64 {
+    fn to_total(&self) -> Option<Total> {
+        Some(Total::Float(*self))
+    }
+}
+
+impl Element for bool {}
+impl Element for char {}
+impl Element for String {}
@@ -0,0 +1,100 @@
+use core::error::Error;
+use core::fmt;
+
+/// Why a token could not be parsed as an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputErrorKind {
+    /// The token is not written the way the element type expects.
+    Malformed,
+    /// The token is a well-formed number that does not fit the element type.
+    OutOfRange,
+}
+
+/// Errors returned by stack operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackError {
+    /// A push was attempted on a stack that already holds `capacity` elements.
+    Overflow { capacity: usize },
+    /// An element was requested from an empty stack.
+    Underflow,
+    /// An operation on the top `needed` elements found only `len`.
+    TooFewElements { needed: usize, len: usize },
+    /// The token at `position` (1-based) could not be parsed as an element.
+    InvalidInput {
+        position: usize,
+        kind: InputErrorKind,
+    },
+    /// The stack was closed and will accept no more elements.
+    Closed,
+    /// No element became available before the timeout elapsed.
+    TimedOut,
+    /// A transaction was begun while another one was still open.
+    TransactionInProgress,
+    /// A transaction operation was used outside of a transaction.
+    NoTransaction,
+    /// The savepoint does not belong to the open transaction, or was
+    /// discarded by rolling back past it.
+    UnknownSavepoint,
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackError::Overflow { capacity } => {
+                write!(f, "stack is full (capacity {})", capacity)
+            }
+            StackError::Underflow => write!(f, "stack is empty"),
+            StackError::TooFewElements { needed, len } => write!(
+                f,
+                "stack holds {} element(s) but {} are needed",
+                len, needed
+            ),
+            StackError::InvalidInput {
+                position,
+                kind: InputErrorKind::Malformed,
+            } => write!(f, "invalid input at token {}", position),
+            StackError::InvalidInput {
+                position,
+                kind: InputErrorKind::OutOfRange,
+            } => write!(f, "token {} is out of range", position),
+            StackError::Closed => write!(f, "stack is closed"),
+            StackError::TimedOut => write!(f, "timed out waiting for the stack"),
+            StackError::TransactionInProgress => write!(f, "a transaction is already open"),
+            StackError::NoTransaction => write!(f, "no transaction is open"),
+            StackError::UnknownSavepoint => write!(f, "unknown savepoint"),
+        }
+    }
+}
+
+impl Error for StackError {}
+
+/// A token refused by an all-or-nothing batch push, with its 1-based position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectedToken {
+    pub position: usize,
+    pub error: StackError,
+}
+
+/// Returned when a batch push was refused, leaving the stack untouched.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchError {
+    pub rejected: Vec<RejectedToken>,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "batch rejected: ")?;
+        for (index, rejected) in self.rejected.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "token {}: {}", rejected.position, rejected.error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for BatchError {}
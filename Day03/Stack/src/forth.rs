@@ -0,0 +1,223 @@
+//! The stack manipulation words of Forth, as methods on [`Stack`].
+//!
+//! Each word is documented with its Forth stack effect: the elements it
+//! takes from the top and the ones it leaves there, both written bottom
+//! first. A word that needs more elements than the stack holds fails with
+//! [`StackError::TooFewElements`], and one that leaves more than it takes
+//! fails with [`StackError::Overflow`] on a stack without room for them,
+//! whatever the overflow policy, unless the policy is
+//! [`OverflowPolicy::Grow`]. Either way the stack is left untouched.
+
+use std::fmt;
+
+use crate::error::StackError;
+use crate::stack::{OverflowPolicy, Stack};
+
+/// A stack manipulation word, as applied by [`Stack::word`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Word {
+    Dup,
+    Drop,
+    Swap,
+    Over,
+    Rot,
+    /// Forth's `-rot`.
+    MinusRot,
+    Nip,
+    Tuck,
+    Pick(usize),
+    Roll(usize),
+    /// Forth's `2dup`.
+    TwoDup,
+    /// Forth's `2swap`.
+    TwoSwap,
+}
+
+impl Word {
+    /// Returns the Forth name of the word, without its argument.
+    pub fn name(self) -> &'static str {
+        match self {
+            Word::Dup => "dup",
+            Word::Drop => "drop",
+            Word::Swap => "swap",
+            Word::Over => "over",
+            Word::Rot => "rot",
+            Word::MinusRot => "-rot",
+            Word::Nip => "nip",
+            Word::Tuck => "tuck",
+            Word::Pick(_) => "pick",
+            Word::Roll(_) => "roll",
+            Word::TwoDup => "2dup",
+            Word::TwoSwap => "2swap",
+        }
+    }
+
+    /// Returns how many elements the word takes from the top of the stack.
+    pub fn takes(self) -> usize {
+        match self {
+            Word::Dup | Word::Drop => 1,
+            Word::Swap | Word::Over | Word::Nip | Word::Tuck | Word::TwoDup => 2,
+            Word::Rot | Word::MinusRot => 3,
+            Word::TwoSwap => 4,
+            Word::Pick(n) | Word::Roll(n) => n.saturating_add(1),
+        }
+    }
+
+    /// Looks up a word that takes no argument by its Forth name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let word = match name {
+            "dup" => Word::Dup,
+            "drop" => Word::Drop,
+            "swap" => Word::Swap,
+            "over" => Word::Over,
+            "rot" => Word::Rot,
+            "-rot" => Word::MinusRot,
+            "nip" => Word::Nip,
+            "tuck" => Word::Tuck,
+            "2dup" => Word::TwoDup,
+            "2swap" => Word::TwoSwap,
+            _ => return None,
+        };
+        Some(word)
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Word::Pick(n) | Word::Roll(n) => write!(f, "{} {}", self.name(), n),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+impl<T> Stack<T> {
+    /// `( a -- )`
+    ///
+    /// Named so as not to be confused with dropping the stack itself.
+    pub fn drop_top(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(1, 0, Vec::clear)
+    }
+
+    /// `( a b -- b a )`
+    pub fn swap(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(2, 2, |top| top.swap(0, 1))
+    }
+
+    /// `( a b c -- b c a )`
+    pub fn rot(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(3, 3, |top| top.rotate_left(1))
+    }
+
+    /// `( a b c -- c a b )`, Forth's `-rot`.
+    pub fn minus_rot(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(3, 3, |top| top.rotate_right(1))
+    }
+
+    /// `( a b -- b )`
+    pub fn nip(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(2, 1, |top| {
+            top.remove(0);
+        })
+    }
+
+    /// `( xn ... x0 -- xn-1 ... x0 xn )`: moves the element `n` below the
+    /// top to the top, so `roll 1` is `swap` and `roll 2` is `rot`.
+    pub fn roll(&mut self, n: usize) -> Result<(), StackError> {
+        let takes = n.saturating_add(1);
+        self.rewrite_top(takes, takes, |top| top.rotate_left(1))
+    }
+
+    /// `( a b c d -- c d a b )`, Forth's `2swap`.
+    pub fn two_swap(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(4, 4, |top| top.rotate_left(2))
+    }
+
+    /// Replaces the top `takes` elements with the `leaves` elements
+    /// `rewrite` makes of them, both bottom first.
+    ///
+    /// The elements are popped and pushed back, so an open transaction can
+    /// undo the word.
+    fn rewrite_top(
+        &mut self,
+        takes: usize,
+        leaves: usize,
+        rewrite: impl FnOnce(&mut Vec<T>),
+    ) -> Result<(), StackError> {
+        let len = self.len();
+        if len < takes {
+            return Err(StackError::TooFewElements { needed: takes, len });
+        }
+        if leaves > takes
+            && self.policy() != OverflowPolicy::Grow
+            && len - takes + leaves > self.capacity()
+        {
+            return Err(StackError::Overflow {
+                capacity: self.capacity(),
+            });
+        }
+
+        let mut top = Vec::with_capacity(leaves.max(takes));
+        while top.len() < takes {
+            top.push(self.pop()?);
+        }
+        top.reverse();
+        rewrite(&mut top);
+        debug_assert_eq!(top.len(), leaves);
+
+        for element in top {
+            // Cannot fail or displace anything: the room was checked above.
+            let _ = self.push_displacing(element);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Clone> Stack<T> {
+    /// `( a -- a a )`
+    pub fn dup(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(1, 2, |top| top.push(top[0].clone()))
+    }
+
+    /// `( a b -- a b a )`
+    pub fn over(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(2, 3, |top| top.push(top[0].clone()))
+    }
+
+    /// `( a b -- b a b )`
+    pub fn tuck(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(2, 3, |top| top.insert(0, top[1].clone()))
+    }
+
+    /// `( xn ... x0 -- xn ... x0 xn )`: copies the element `n` below the
+    /// top to the top, so `pick 0` is `dup` and `pick 1` is `over`.
+    pub fn pick(&mut self, n: usize) -> Result<(), StackError> {
+        let takes = n.saturating_add(1);
+        self.rewrite_top(takes, takes.saturating_add(1), |top| {
+            top.push(top[0].clone())
+        })
+    }
+
+    /// `( a b -- a b a b )`, Forth's `2dup`.
+    pub fn two_dup(&mut self) -> Result<(), StackError> {
+        self.rewrite_top(2, 4, |top| top.extend_from_within(..))
+    }
+
+    /// Applies `word` with the method it stands for.
+    pub fn word(&mut self, word: Word) -> Result<(), StackError> {
+        match word {
+            Word::Dup => self.dup(),
+            Word::Drop => self.drop_top(),
+            Word::Swap => self.swap(),
+            Word::Over => self.over(),
+            Word::Rot => self.rot(),
+            Word::MinusRot => self.minus_rot(),
+            Word::Nip => self.nip(),
+            Word::Tuck => self.tuck(),
+            Word::Pick(n) => self.pick(n),
+            Word::Roll(n) => self.roll(n),
+            Word::TwoDup => self.two_dup(),
+            Word::TwoSwap => self.two_swap(),
+        }
+    }
+}
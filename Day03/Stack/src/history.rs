@@ -0,0 +1,117 @@
+use std::time::SystemTime;
+
+use crate::error::StackError;
+use crate::op::{Op, Outcome};
+use crate::stack::Stack;
+
+/// One op applied to a stack, and what it did.
+#[derive(Debug, Clone)]
+pub struct Event<T> {
+    /// The position of the event in its [`History`], starting from 1.
+    pub seq: u64,
+    pub op: Op<T>,
+    pub result: Result<Outcome<T>, StackError>,
+    pub timestamp: SystemTime,
+}
+
+/// Every op applied to a stack since a starting point, so the stack can be
+/// rebuilt as it was after any of them.
+///
+/// A `History` only records: the ops are applied to a stack the caller owns,
+/// either through [`History::apply`] or by the caller followed by
+/// [`History::record`]. Either way that stack must be the one the history
+/// started from, with no changes made behind the history's back.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    base: Stack<T>,
+    base_seq: u64,
+    events: Vec<Event<T>>,
+}
+
+impl<T: Clone> History<T> {
+    /// Starts a history from the current state of `stack`.
+    pub fn new(stack: &Stack<T>) -> Self {
+        History {
+            base: stack.clone(),
+            base_seq: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Applies `op` to `stack` and records it.
+    pub fn apply(&mut self, stack: &mut Stack<T>, op: Op<T>) -> Result<Outcome<T>, StackError> {
+        let result = stack.apply(op.clone());
+        self.record(op, result.clone());
+        result
+    }
+
+    /// Records `op`, already applied with `result`, returning its sequence
+    /// number.
+    pub fn record(&mut self, op: Op<T>, result: Result<Outcome<T>, StackError>) -> u64 {
+        let seq = self.last_seq() + 1;
+        self.events.push(Event {
+            seq,
+            op,
+            result,
+            timestamp: SystemTime::now(),
+        });
+        seq
+    }
+
+    /// Forgets every event and starts again from `stack`, which replaces
+    /// the old one wholesale. Sequence numbers carry on from the last event.
+    pub fn rebase(&mut self, stack: &Stack<T>) {
+        self.base = stack.clone();
+        self.base_seq = self.last_seq();
+        self.events.clear();
+    }
+
+    /// Rebuilds the stack as it was right after event `seq`, or as it was at
+    /// the start when `seq` is [`History::first_seq`] minus one.
+    ///
+    /// Returns `None` if `seq` is before the start or after the last event.
+    pub fn replay_to(&self, seq: u64) -> Option<Stack<T>> {
+        if seq < self.base_seq || seq > self.last_seq() {
+            return None;
+        }
+
+        let mut stack = self.base.clone();
+        for event in &self.events[..(seq - self.base_seq) as usize] {
+            // Failed events failed without changing anything, and fail
+            // again the same way.
+            let _ = stack.apply(event.op.clone());
+        }
+        Some(stack)
+    }
+}
+
+impl<T> History<T> {
+    /// Returns the event with sequence number `seq`.
+    pub fn get(&self, seq: u64) -> Option<&Event<T>> {
+        let index = seq.checked_sub(self.base_seq + 1)?;
+        self.events.get(index as usize)
+    }
+
+    /// Returns the sequence number of the first recorded event.
+    pub fn first_seq(&self) -> u64 {
+        self.base_seq + 1
+    }
+
+    /// Returns the sequence number of the last event, or of the start if
+    /// there are none.
+    pub fn last_seq(&self) -> u64 {
+        self.base_seq + self.events.len() as u64
+    }
+
+    pub fn events(&self) -> &[Event<T>] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{vec_deque, VecDeque};
+use std::iter::{FromIterator, Rev};
+
+use crate::stack::{OverflowPolicy, Stack};
+
+/// Borrowing iterator over a stack from top to bottom, created by [`Stack::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    inner: Rev<vec_deque::Iter<'a, T>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(crate) fn new(elements: &'a VecDeque<T>) -> Self {
+        Iter {
+            inner: elements.iter().rev(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// Owning iterator over a stack from top to bottom, created by `into_iter`.
+#[derive(Debug, Clone)]
+pub struct IntoIter<T> {
+    inner: Rev<vec_deque::IntoIter<T>>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+/// Iterator that pops every element of a stack, created by [`Stack::drain`].
+///
+/// Elements that are not iterated over are still removed when it is dropped.
+#[derive(Debug)]
+pub struct Drain<'a, T> {
+    inner: Rev<vec_deque::Drain<'a, T>>,
+}
+
+impl<'a, T> Drain<'a, T> {
+    pub(crate) fn new(drain: vec_deque::Drain<'a, T>) -> Self {
+        Drain { inner: drain.rev() }
+    }
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.into_elements().into_iter().rev(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stack<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Pushes every element in order, so the last one ends up on top.
+///
+/// Panics if the stack's overflow policy refuses an element; use
+/// [`Stack::push_batch`] to be told about them instead.
+impl<T> Extend<T> for Stack<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, elements: I) {
+        for element in elements {
+            if let Err(err) = self.push(element) {
+                panic!("cannot extend the stack: {}", err);
+            }
+        }
+    }
+}
+
+/// Builds a stack with the last element on top, sized to the iterator and
+/// growing as needed afterwards.
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(elements: I) -> Self {
+        let elements: Vec<T> = elements.into_iter().collect();
+        let mut stack = Stack::with_policy(elements.len(), OverflowPolicy::Grow);
+        stack.extend(elements);
+        stack
+    }
+}
@@ -0,0 +1,84 @@
+//! Stack types built up from the Day 3 stack exercise.
+//!
+//! Everything except [`ArrayStack`] and the error types needs the default
+//! `std` feature; without it the crate is `#![no_std]` and does not allocate.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod array;
+#[cfg(feature = "std")]
+pub mod blocking;
+#[cfg(feature = "std")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod concurrent;
+#[cfg(feature = "std")]
+pub mod element;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod forth;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod iter;
+#[cfg(feature = "std")]
+pub mod minmax;
+#[cfg(feature = "std")]
+pub mod multi;
+#[cfg(feature = "std")]
+pub mod op;
+#[cfg(feature = "std")]
+pub mod parse;
+#[cfg(feature = "std")]
+pub mod persistent;
+#[cfg(feature = "std")]
+pub mod segmented;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod spill;
+#[cfg(feature = "std")]
+pub mod stack;
+#[cfg(feature = "std")]
+pub mod transaction;
+#[cfg(feature = "std")]
+pub mod undo;
+#[cfg(feature = "std")]
+pub mod wal;
+
+pub use array::ArrayStack;
+#[cfg(feature = "std")]
+pub use blocking::BlockingStack;
+#[cfg(feature = "std")]
+pub use codec::Codec;
+#[cfg(feature = "std")]
+pub use concurrent::ConcurrentStack;
+#[cfg(feature = "std")]
+pub use element::{Element, Radix, Total};
+#[cfg(feature = "std")]
+pub use error::BatchError;
+pub use error::{InputErrorKind, RejectedToken, StackError};
+#[cfg(feature = "std")]
+pub use forth::Word;
+#[cfg(feature = "std")]
+pub use history::{Event, History};
+#[cfg(feature = "std")]
+pub use minmax::{MinMaxStack, Summary};
+#[cfg(feature = "std")]
+pub use multi::MultiStack;
+#[cfg(feature = "std")]
+pub use op::{Op, Outcome};
+#[cfg(feature = "std")]
+pub use persistent::PersistentStack;
+#[cfg(feature = "std")]
+pub use segmented::SegmentedStack;
+#[cfg(feature = "std")]
+pub use spill::{SpillError, SpillStack};
+#[cfg(feature = "std")]
+pub use stack::{OverflowPolicy, Stack};
+#[cfg(feature = "std")]
+pub use transaction::Savepoint;
+#[cfg(feature = "std")]
+pub use undo::{Command, UndoManager};
+#[cfg(feature = "std")]
+pub use wal::{DurableStack, WalError};
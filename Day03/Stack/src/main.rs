@@ -1,84 +1,1193 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use rust::parse::{parse_batch, parse_line, InvalidTokenPolicy};
+use rust::snapshot::{self, Format};
+use rust::wal::{SyncPolicy, WalOptions, SNAPSHOT_FILE};
+use rust::{
+    Codec, DurableStack, Element, Event, History, Op, Outcome, OverflowPolicy, Radix,
+    RejectedToken, Savepoint, Stack, StackError, Summary, WalError, Word,
+};
+
+const HELP: &str = "\
+Commands:
+  push <v>...       push one or more values
+  pop [n]           pop the top element, or the top n elements
+  peek              show the top element
+  show              show every element, top first
+  size              show how many elements are on the stack
+  min, max          show the smallest or largest element
+  sum, mean         show the total or average of numeric elements
+  clear             remove every element
+  begin             open a transaction
+  savepoint         mark the current state of the transaction
+  rollback [s]      undo the transaction, or everything since savepoint s
+  commit            keep everything done in the transaction
+  create <s> [c]    create stack s with capacity c, or that of the selected stack
+  list              list the stacks, marking the selected one with *
+  select <s>        make stack s the one the other commands apply to
+  delete <s>        delete stack s
+  move <s> <t> [n]  move the top n elements (default 1) from stack s to stack t
+  copy <s> <t> [n]  copy the top n elements from stack s onto stack t
+  swap <s> <t> [n]  exchange the top n elements of stacks s and t
+  dup, drop, swap, over, rot, -rot, nip, tuck, 2dup, 2swap
+                    rearrange the top elements as the Forth word of that name does
+  pick <n>          copy the element n below the top onto the top
+  roll <n>          move the element n below the top to the top
+  save <f> [t]      save the stack to file f as binary (default) or text
+  load <f>          replace the stack with the one saved in file f
+  atomic [b]        show or set whether push applies a whole line or nothing: on or off
+  invalid [p]       show or set what a non-atomic push does with bad tokens: skip or reject
+  base [b]          show or set the base integers are shown in: 2, 8, 10 or 16
+  history           list every change made to the stack, numbered
+  goto <n>          show the stack as it was after change n, or at the start for 0
+  help              show this message
+  quit              leave the program";
+
+const USAGE: &str = "\
+Usage: rust [--type <type>] [--overflow <policy>] [--wal <dir> [--sync <when>]]
+
+Options:
+  --type <type>        element type: i32 (default), i64, u64, f64, char or string
+  --overflow <policy>  what push does on a full stack:
+                       reject (default), grow, evict-bottom or overwrite-top
+  --wal <dir>          keep the stacks in directory dir, logging every change
+                       so they survive restarts and crashes
+  --sync <when>        when the log is flushed to disk: always (default),
+                       never, or a number n to flush every n changes";
+
+struct Settings {
+    atomic: bool,
+    invalid: InvalidTokenPolicy,
+    radix: Radix,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ElementType {
+    I32,
+    I64,
+    U64,
+    F64,
+    Char,
+    String,
+}
+
+struct Options {
+    element: ElementType,
+    overflow: OverflowPolicy,
+    wal: Option<PathBuf>,
+    sync: SyncPolicy,
+}
+
+/// Where the stack of a session lives: only in memory, or also in the log
+/// of a `--wal` directory.
+enum Store<T> {
+    Memory(Stack<T>),
+    Durable(DurableStack<T>),
+}
+
+impl<T> Store<T> {
+    fn stack(&self) -> &Stack<T> {
+        match self {
+            Store::Memory(stack) => stack,
+            Store::Durable(durable) => durable.stack(),
+        }
+    }
+}
+
+impl<T: Codec + Clone> Store<T> {
+    /// Applies `op` with `apply`, logging it first if there is a log.
+    ///
+    /// A failed compaction of the log is only reported, since the op itself
+    /// was applied and logged.
+    fn apply_with(
+        &mut self,
+        op: Op<T>,
+        apply: impl FnOnce(&mut Stack<T>, Op<T>) -> Result<Outcome<T>, StackError>,
+    ) -> Result<Outcome<T>, WalError> {
+        match self {
+            Store::Memory(stack) => Ok(apply(stack, op)?),
+            Store::Durable(durable) => {
+                let result = durable.apply_with(op, apply);
+                if let Some(err) = durable.take_compaction_error() {
+                    println!("Warning: the log could not be compacted: {}", err);
+                }
+                result
+            }
+        }
+    }
+
+    fn replace(&mut self, stack: Stack<T>) -> Result<(), WalError> {
+        match self {
+            Store::Memory(old) => *old = stack,
+            Store::Durable(durable) => durable.replace(stack)?,
+        }
+        Ok(())
+    }
+}
+
+/// A stack being edited, and the ops applied to it.
+struct Session<T> {
+    store: Store<T>,
+    history: History<T>,
+    /// The `min`, `max`, `sum` and `mean` of the stack.
+    summary: Summary<T>,
+    /// The savepoints of the open transaction, which the user refers to by
+    /// their 1-based position.
+    savepoints: Vec<Savepoint>,
+}
+
+impl<T> Session<T> {
+    fn stack(&self) -> &Stack<T> {
+        self.store.stack()
+    }
+}
+
+impl<T: Element + Codec + PartialOrd + Clone> Session<T> {
+    fn new(store: Store<T>) -> Self {
+        Session {
+            history: History::new(store.stack()),
+            summary: Summary::new(store.stack()),
+            store,
+            savepoints: Vec::new(),
+        }
+    }
+
+    /// Logs `op` if there is a log, then applies it and records it in the
+    /// history and the summary.
+    fn apply(&mut self, op: Op<T>) -> Result<Outcome<T>, WalError> {
+        self.apply_with(op, Stack::apply)
+    }
+
+    /// Like [`Session::apply`], but applies `op` with `apply`, which must do
+    /// what [`Stack::apply`] does.
+    fn apply_with(
+        &mut self,
+        op: Op<T>,
+        apply: impl FnOnce(&mut Stack<T>, Op<T>) -> Result<Outcome<T>, StackError>,
+    ) -> Result<Outcome<T>, WalError> {
+        let unchanged = Summary::unchanged_by(self.stack(), &op);
+        let history = &mut self.history;
+        let outcome = self.store.apply_with(op, |stack, op| {
+            let outcome = apply(stack, op.clone());
+            history.record(op, outcome.clone());
+            outcome
+        });
+        self.summary.sync(self.store.stack(), unchanged);
+        outcome
+    }
+
+    /// Replaces the stack, making the new one the log's snapshot and the
+    /// start of the history.
+    fn replace(&mut self, stack: Stack<T>) -> Result<(), WalError> {
+        self.store.replace(stack)?;
+        self.history.rebase(self.store.stack());
+        self.summary = Summary::new(self.store.stack());
+        self.savepoints.clear();
+        Ok(())
+    }
+
+    /// Pushes every element of `elements` or none of them, returning the
+    /// ones the stack refused. The batch is logged and recorded like any
+    /// other op.
+    fn push_batch(&mut self, elements: Vec<T>) -> Result<Vec<RejectedToken>, WalError> {
+        let mut rejected = Vec::new();
+        let result = self.apply_with(Op::PushBatch(elements), |stack, op| match op {
+            Op::PushBatch(elements) => match stack.push_batch(elements) {
+                Ok(()) => Ok(Outcome::Done),
+                Err(err) => {
+                    let first = err.rejected[0].error;
+                    rejected = err.rejected;
+                    Err(first)
+                }
+            },
+            op => stack.apply(op),
+        });
+        match result {
+            Err(WalError::Stack(_)) if !rejected.is_empty() => Ok(rejected),
+            result => result.map(|_| rejected),
+        }
+    }
+
+    /// Pops the top `count` elements, returning them from bottom to top.
+    fn pop_top(&mut self, count: usize) -> Result<Vec<T>, WalError> {
+        let mut popped = Vec::with_capacity(count);
+        for _ in 0..count {
+            if let Outcome::Element(element) = self.apply(Op::Pop)? {
+                popped.push(element);
+            }
+        }
+        popped.reverse();
+        Ok(popped)
+    }
+}
+
+/// The name of the stack the program starts with.
+const FIRST_STACK: &str = "main";
+
+/// The directory inside the `--wal` directory holding the log directory of
+/// every stack created with `create`. The first stack is kept in the `--wal`
+/// directory itself.
+const STACKS_DIR: &str = "stacks";
+
+/// Every named stack, and the one commands apply to.
+struct Workspace<T> {
+    stacks: BTreeMap<String, Session<T>>,
+    selected: String,
+    /// The `--wal` directory and how to log to it, if there is one.
+    wal: Option<(PathBuf, WalOptions)>,
+}
+
+impl<T> Workspace<T> {
+    fn new(first: Session<T>, wal: Option<(PathBuf, WalOptions)>) -> Self {
+        let mut stacks = BTreeMap::new();
+        stacks.insert(FIRST_STACK.to_string(), first);
+        Workspace {
+            stacks,
+            selected: FIRST_STACK.to_string(),
+            wal,
+        }
+    }
+
+    /// Returns the log directory of the stack called `name`, if the stacks
+    /// are logged.
+    fn wal_dir(&self, name: &str) -> Option<PathBuf> {
+        let (dir, _) = self.wal.as_ref()?;
+        Some(dir.join(STACKS_DIR).join(name))
+    }
+
+    fn current(&mut self) -> &mut Session<T> {
+        self.stacks
+            .get_mut(&self.selected)
+            .expect("the selected stack exists")
+    }
+
+    /// Returns the stack called `name`, which the caller checked exists.
+    fn named(&mut self, name: &str) -> &mut Session<T> {
+        self.stacks
+            .get_mut(name)
+            .expect("the stack was checked to exist")
+    }
+
+    /// Returns the stack called `name`, printing an error if there is none.
+    fn get_mut(&mut self, name: &str) -> Option<&mut Session<T>> {
+        let session = self.stacks.get_mut(name);
+        if session.is_none() {
+            println!("Error: there is no stack named `{}`", name);
+        }
+        session
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        element: ElementType::I32,
+        overflow: OverflowPolicy::Reject,
+        wal: None,
+        sync: SyncPolicy::Always,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--type" => {
+                let value = args.next().ok_or("--type needs a value")?;
+                options.element = parse_element_type(&value)?;
+            }
+            "--overflow" => {
+                let value = args.next().ok_or("--overflow needs a value")?;
+                options.overflow = parse_overflow_policy(&value)?;
+            }
+            "--wal" => {
+                let value = args.next().ok_or("--wal needs a value")?;
+                options.wal = Some(PathBuf::from(value));
+            }
+            "--sync" => {
+                let value = args.next().ok_or("--sync needs a value")?;
+                options.sync = parse_sync_policy(&value)?;
+            }
+            _ => return Err(format!("unknown argument `{}`", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_element_type(value: &str) -> Result<ElementType, String> {
+    match value {
+        "i32" => Ok(ElementType::I32),
+        "i64" => Ok(ElementType::I64),
+        "u64" => Ok(ElementType::U64),
+        "f64" => Ok(ElementType::F64),
+        "char" => Ok(ElementType::Char),
+        "string" => Ok(ElementType::String),
+        _ => Err(format!("unknown element type `{}`", value)),
+    }
+}
+
+fn parse_overflow_policy(value: &str) -> Result<OverflowPolicy, String> {
+    OverflowPolicy::from_name(value).ok_or_else(|| format!("unknown overflow policy `{}`", value))
+}
+
+fn parse_sync_policy(value: &str) -> Result<SyncPolicy, String> {
+    match value {
+        "always" => Ok(SyncPolicy::Always),
+        "never" => Ok(SyncPolicy::Never),
+        _ => match value.parse() {
+            Ok(n) if n > 0 => Ok(SyncPolicy::Every(n)),
+            _ => Err(format!("unknown sync policy `{}`", value)),
+        },
+    }
+}
+
 fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match options.element {
+        ElementType::I32 => run::<i32>(&options),
+        ElementType::I64 => run::<i64>(&options),
+        ElementType::U64 => run::<u64>(&options),
+        ElementType::F64 => run::<f64>(&options),
+        ElementType::Char => run::<char>(&options),
+        ElementType::String => run::<String>(&options),
+    }
+}
+
+fn run<T: Element + Codec + PartialOrd + Clone>(options: &Options) {
+    let mut workspace = match open_workspace::<T>(options) {
+        Some(workspace) => workspace,
+        None => return,
+    };
+    let mut settings = Settings {
+        atomic: true,
+        invalid: InvalidTokenPolicy::Reject,
+        radix: Radix::Decimal,
+    };
+
+    println!("Type `help` to see the available commands");
+
+    while let Some(line) = read_line("> ") {
+        let line = line.trim();
+        let (command, args) = match line.split_once(char::is_whitespace) {
+            Some((command, args)) => (command, args.trim()),
+            None => (line, ""),
+        };
+
+        let session = workspace.current();
+        match command {
+            "" => continue,
+            "push" => push(session, args, &settings),
+            "pop" => pop(session, args, settings.radix),
+            "peek" => peek(session.stack(), settings.radix),
+            "show" => display(session.stack(), settings.radix),
+            "size" => println!(
+                "{} of {} slots used",
+                session.stack().len(),
+                session.stack().capacity()
+            ),
+            "min" | "max" | "sum" | "mean" => stats(session, command, settings.radix),
+            "clear" => match session.apply(Op::Clear) {
+                Ok(_) => println!("The stack has been cleared"),
+                Err(err) => println!("Error: {}", err),
+            },
+            "save" => save(session.stack(), args),
+            "load" => load(session, args),
+            "begin" | "savepoint" | "rollback" | "commit" => transaction(session, command, args),
+            "create" => create(&mut workspace, args, options.overflow),
+            "list" => list(&workspace),
+            "select" => select(&mut workspace, args),
+            "delete" => delete(&mut workspace, args),
+            "move" | "copy" => transfer(&mut workspace, command, args),
+            "swap" if !args.is_empty() => transfer(&mut workspace, command, args),
+            "dup" | "drop" | "swap" | "over" | "rot" | "-rot" | "nip" | "tuck" | "pick"
+            | "roll" | "2dup" | "2swap" => word(session, command, args, settings.radix),
+            "atomic" => set_atomic(&mut settings.atomic, args),
+            "invalid" => set_policy(&mut settings.invalid, args),
+            "base" => set_radix(&mut settings.radix, args),
+            "history" => history(&session.history, settings.radix),
+            "goto" => goto(&session.history, args, settings.radix),
+            "help" => println!("{}", HELP),
+            "quit" | "exit" => break,
+            _ => println!(
+                "Unknown command `{}`. Type `help` to see the available commands",
+                command
+            ),
+        }
+    }
+}
+
+/// Prints `prompt` and reads one line, returning `None` at end of input.
+fn read_line(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    if let Err(err) = std::io::stdout().flush() {
+        eprintln!("Failed to flush output: {}", err);
+    }
+
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) => {
+            println!();
+            None
+        }
+        Ok(_) => Some(line),
+        Err(err) => {
+            eprintln!("Failed to read input: {}", err);
+            None
+        }
+    }
+}
+
+/// Creates the first stack, or reopens every stack kept in the `--wal`
+/// directory.
+fn open_workspace<T: Element + Codec + PartialOrd + Clone>(
+    options: &Options,
+) -> Option<Workspace<T>> {
+    let dir = match &options.wal {
+        Some(dir) => dir,
+        None => {
+            let capacity = read_capacity()?;
+            let first = Stack::with_policy(capacity, options.overflow);
+            return Some(Workspace::new(Session::new(Store::Memory(first)), None));
+        }
+    };
+
+    let existing = dir.join(SNAPSHOT_FILE).exists();
+    let capacity = if existing { 0 } else { read_capacity()? };
+    let wal_options = WalOptions {
+        sync: options.sync,
+        ..WalOptions::default()
+    };
+
+    let first = open_durable(dir, capacity, options.overflow, wal_options)?;
+    if existing {
+        println!(
+            "Recovered {} element(s) from {}",
+            first.stack().len(),
+            dir.display()
+        );
+    }
+    let mut workspace = Workspace::new(first, Some((dir.clone(), wal_options)));
+
+    let mut names = match fs::read_dir(dir.join(STACKS_DIR)) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    for name in names {
+        let session = open_durable(
+            &dir.join(STACKS_DIR).join(&name),
+            0,
+            options.overflow,
+            wal_options,
+        )?;
+        println!(
+            "Recovered stack `{}` with {} element(s)",
+            name,
+            session.stack().len()
+        );
+        workspace.stacks.insert(name, session);
+    }
+    Some(workspace)
+}
+
+/// Opens the stack logged in `dir`, printing an error if it cannot.
+fn open_durable<T: Element + Codec + PartialOrd + Clone>(
+    dir: &Path,
+    capacity: usize,
+    policy: OverflowPolicy,
+    options: WalOptions,
+) -> Option<Session<T>> {
+    match DurableStack::open(dir, capacity, policy, options) {
+        Ok(durable) => Some(Session::new(Store::Durable(durable))),
+        Err(err) => {
+            eprintln!("Error: {}: {}", dir.display(), err);
+            None
+        }
+    }
+}
+
+fn read_capacity() -> Option<usize> {
     println!("Enter the maximum capacity for the stack:");
-    let mut capacity = String::new();
 
-    std::io::stdin()
-        .read_line(&mut capacity)
-        .expect("Failed to read input");
+    loop {
+        let line = read_line("")?;
+        match line.trim().parse() {
+            Ok(capacity) => return Some(capacity),
+            Err(_) => println!(
+                "`{}` is not a valid capacity. Please enter a non-negative whole number:",
+                line.trim()
+            ),
+        }
+    }
+}
 
-    let capacity = capacity.trim().parse().expect("Invalid input");
-    let mut numbers: Vec<i32> = Vec::with_capacity(capacity);
-    let mut head: usize = 0;
+/// Saves the stack to the path in `args`, which may contain spaces like the
+/// one `load` takes, followed by an optional format.
+fn save<T: Element + Codec>(stack: &Stack<T>, args: &str) {
+    let (path, format) = match args.rsplit_once(char::is_whitespace) {
+        Some((path, "binary")) => (path.trim_end(), Format::Binary),
+        Some((path, "text")) => (path.trim_end(), Format::Text),
+        _ => (args, Format::Binary),
+    };
+    if path.is_empty() {
+        println!("Usage: save <file> [binary|text]");
+        return;
+    }
 
-    push(&mut numbers, &mut head, capacity);
+    match snapshot::save(stack, path, format) {
+        Ok(()) => println!("Saved {} element(s) to {}", stack.len(), path),
+        Err(err) => println!("Error: {}", err),
+    }
+}
+
+/// Replaces the stack of `session` with the one saved at the path in `args`.
+fn load<T: Element + Codec + PartialOrd + Clone>(session: &mut Session<T>, args: &str) {
+    if args.is_empty() {
+        println!("Usage: load <file>");
+        return;
+    }
+
+    let loaded = match snapshot::load(args) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            println!("Error: {}", err);
+            return;
+        }
+    };
 
+    let len = loaded.len();
+    match session.replace(loaded) {
+        Ok(()) => println!("Loaded {} element(s) from {}", len, args),
+        Err(err) => println!("Error: {}", err),
+    }
+}
+
+fn transaction<T: Element + Codec + PartialOrd + Clone>(
+    session: &mut Session<T>,
+    command: &str,
+    args: &str,
+) {
+    let result = match command {
+        "begin" => session
+            .apply(Op::Begin)
+            .map(|_| println!("Transaction opened")),
+        "savepoint" => session.apply(Op::Savepoint).map(|outcome| {
+            if let Outcome::Savepoint(savepoint) = outcome {
+                session.savepoints.push(savepoint);
+            }
+            println!("Savepoint {} created", session.savepoints.len());
+        }),
+        "rollback" if args.is_empty() => session.apply(Op::Rollback).map(|_| {
+            session.savepoints.clear();
+            println!("Transaction rolled back");
+        }),
+        "rollback" => {
+            let index = match args.parse::<usize>() {
+                Ok(index) if index >= 1 && index <= session.savepoints.len() => index,
+                _ => {
+                    println!(
+                        "Usage: rollback [savepoint], where savepoint is 1 to {}",
+                        session.savepoints.len()
+                    );
+                    return;
+                }
+            };
+            session
+                .apply(Op::RollbackTo(session.savepoints[index - 1]))
+                .map(|_| {
+                    session.savepoints.truncate(index);
+                    println!("Rolled back to savepoint {}", index);
+                })
+        }
+        _ => session.apply(Op::Commit).map(|_| {
+            session.savepoints.clear();
+            println!("Transaction committed");
+        }),
+    };
+
+    if let Err(err) = result {
+        println!("Error: {}", err);
+    }
+}
+
+fn create<T: Element + Codec + PartialOrd + Clone>(
+    workspace: &mut Workspace<T>,
+    args: &str,
+    policy: OverflowPolicy,
+) {
+    let mut words = args.split_whitespace();
+    let (name, capacity) = match (words.next(), words.next(), words.next()) {
+        (Some(name), None, None) => (name, Some(workspace.current().stack().capacity())),
+        (Some(name), Some(capacity), None) => (name, capacity.parse().ok()),
+        _ => (args, None),
+    };
+    let capacity = match capacity {
+        Some(capacity) if !name.is_empty() => capacity,
+        _ => {
+            println!("Usage: create <name> [capacity]");
+            return;
+        }
+    };
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        println!("Error: a stack name may only hold letters, digits, `-` and `_`");
+        return;
+    }
+    if workspace.stacks.contains_key(name) {
+        println!("Error: there is already a stack named `{}`", name);
+        return;
+    }
+
+    let store = match (workspace.wal_dir(name), &workspace.wal) {
+        (Some(dir), Some((_, options))) => {
+            match DurableStack::open(dir, capacity, policy, *options) {
+                Ok(durable) => Store::Durable(durable),
+                Err(err) => {
+                    println!("Error: {}", err);
+                    return;
+                }
+            }
+        }
+        _ => Store::Memory(Stack::with_policy(capacity, policy)),
+    };
+    workspace
+        .stacks
+        .insert(name.to_string(), Session::new(store));
     println!(
-        "Top of the stack contains {}",
-        top_of_the_stack(&numbers, head)
+        "Created stack `{}` with room for {} element(s)",
+        name, capacity
     );
+}
 
-    pop(&mut numbers, &mut head);
+fn list<T>(workspace: &Workspace<T>) {
+    let width = workspace.stacks.keys().map(String::len).max().unwrap_or(0);
+    for (name, session) in &workspace.stacks {
+        let marker = if *name == workspace.selected {
+            '*'
+        } else {
+            ' '
+        };
+        println!(
+            "{} {:<width$}  {} of {} slots used",
+            marker,
+            name,
+            session.stack().len(),
+            session.stack().capacity()
+        );
+    }
+}
 
-    pop(&mut numbers, &mut head);
+fn select<T>(workspace: &mut Workspace<T>, args: &str) {
+    if args.is_empty() {
+        println!("Usage: select <name>");
+        return;
+    }
 
-    display(&numbers, head);
+    if workspace.get_mut(args).is_some() {
+        workspace.selected = args.to_string();
+        println!("Selected stack `{}`", args);
+    }
 }
 
-fn push(numbers: &mut Vec<i32>, head: &mut usize, capacity: usize) {
-    println!("Enter the numbers to push into the stack separated by space");
+fn delete<T>(workspace: &mut Workspace<T>, args: &str) {
+    if args.is_empty() {
+        println!("Usage: delete <name>");
+        return;
+    }
+
+    if args == workspace.selected {
+        println!("Error: cannot delete the selected stack `{}`", args);
+        return;
+    }
+
+    if args == FIRST_STACK && workspace.wal.is_some() {
+        println!(
+            "Error: the stack `{}` is kept in the --wal directory itself and cannot be deleted",
+            args
+        );
+        return;
+    }
 
-    let mut user_num = String::new();
+    if workspace.stacks.remove(args).is_none() {
+        println!("Error: there is no stack named `{}`", args);
+        return;
+    }
+
+    if let Some(dir) = workspace.wal_dir(args) {
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            println!(
+                "Error: could not remove {}, so the stack will be back next time: {}",
+                dir.display(),
+                err
+            );
+            return;
+        }
+    }
+    println!("Deleted stack `{}`", args);
+}
 
-    std::io::stdin()
-        .read_line(&mut user_num)
-        .expect("Failed to read input");
+/// Runs `move`, `copy` or `swap`, which take the top elements of one stack
+/// to another, keeping their order.
+fn transfer<T: Element + Codec + PartialOrd + Clone>(
+    workspace: &mut Workspace<T>,
+    command: &str,
+    args: &str,
+) {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let (from, to, count) = match words.as_slice() {
+        [from, to] => (*from, *to, Some(1)),
+        [from, to, count] => (*from, *to, count.parse().ok()),
+        _ => ("", "", None),
+    };
+    let count = match count {
+        Some(count) => count,
+        None => {
+            println!("Usage: {} <from> <to> [n]", command);
+            return;
+        }
+    };
+
+    if from == to {
+        println!("Error: `{}` is both the source and the destination", from);
+        return;
+    }
+
+    // Each stack must hold `needed` elements, gives up `freed` of them and
+    // then receives `received`. Both are checked before anything is popped,
+    // so a refused transfer leaves no trace in the history or the log.
+    let swap = command == "swap";
+    let freed = if command == "copy" { 0 } else { count };
+    let exchanged = if swap { count } else { 0 };
+    let sides = [
+        (from, count, freed, exchanged),
+        (to, exchanged, exchanged, count),
+    ];
+    for (name, needed, freed, received) in sides {
+        let stack = match workspace.get_mut(name) {
+            Some(session) => session.stack(),
+            None => return,
+        };
+        if stack.len() < needed {
+            println!("Error: `{}` holds only {} element(s)", name, stack.len());
+            return;
+        }
+        if let Some(room) = stack.room().map(|room| room + freed) {
+            if room < received {
+                println!("Error: `{}` has room for only {} element(s)", name, room);
+                return;
+            }
+        }
+    }
+
+    let result = match command {
+        "move" => move_top(workspace, from, to, count),
+        "copy" => copy_top(workspace, from, to, count),
+        _ => swap_top(workspace, from, to, count),
+    };
+    match result {
+        Ok(()) => match command {
+            "move" => println!("Moved {} element(s) from `{}` to `{}`", count, from, to),
+            "copy" => println!("Copied {} element(s) from `{}` to `{}`", count, from, to),
+            _ => println!(
+                "Swapped {} element(s) between `{}` and `{}`",
+                count, from, to
+            ),
+        },
+        Err(err) => println!("Error: {}", err),
+    }
+}
+
+fn move_top<T: Element + Codec + PartialOrd + Clone>(
+    workspace: &mut Workspace<T>,
+    from: &str,
+    to: &str,
+    count: usize,
+) -> Result<(), WalError> {
+    let moved = workspace.named(from).pop_top(count)?;
+    let pushed = workspace.named(to).apply(Op::PushBatch(moved.clone()));
+
+    if let Err(err) = pushed {
+        // `transfer` checked there is room, so only the log can fail here.
+        // The elements were just popped, so there is room to put them back.
+        workspace.named(from).apply(Op::PushBatch(moved))?;
+        return Err(err);
+    }
+    Ok(())
+}
+
+fn copy_top<T: Element + Codec + PartialOrd + Clone>(
+    workspace: &mut Workspace<T>,
+    from: &str,
+    to: &str,
+    count: usize,
+) -> Result<(), WalError> {
+    let source = workspace.stacks[from].stack().as_slice();
+    let copied = source[source.len() - count..].to_vec();
+    workspace.named(to).apply(Op::PushBatch(copied))?;
+    Ok(())
+}
+
+fn swap_top<T: Element + Codec + PartialOrd + Clone>(
+    workspace: &mut Workspace<T>,
+    from: &str,
+    to: &str,
+    count: usize,
+) -> Result<(), WalError> {
+    let first = workspace.named(from).pop_top(count)?;
+    let second = workspace.named(to).pop_top(count)?;
+    // Each stack just gave up `count` elements, so both pushes fit.
+    workspace.named(from).apply(Op::PushBatch(second))?;
+    workspace.named(to).apply(Op::PushBatch(first))?;
+    Ok(())
+}
+
+/// Runs one of the Forth stack manipulation words.
+fn word<T: Element + Codec + PartialOrd + Clone>(
+    session: &mut Session<T>,
+    command: &str,
+    args: &str,
+    radix: Radix,
+) {
+    let word = match (command, args.parse::<usize>()) {
+        ("pick", Ok(n)) => Some(Word::Pick(n)),
+        ("roll", Ok(n)) => Some(Word::Roll(n)),
+        (_, _) if args.is_empty() => Word::from_name(command),
+        _ => None,
+    };
+    let word = match word {
+        Some(word) => word,
+        None if command == "pick" || command == "roll" => {
+            println!("Usage: {} <n>", command);
+            return;
+        }
+        None => {
+            println!("Usage: {}", command);
+            return;
+        }
+    };
+
+    match session.apply(Op::Word(word)) {
+        Ok(_) => peek(session.stack(), radix),
+        Err(err) => println!("Error: {}", err),
+    }
+}
+
+fn set_atomic(atomic: &mut bool, args: &str) {
+    match args {
+        "" => {}
+        "on" => *atomic = true,
+        "off" => *atomic = false,
+        _ => {
+            println!("Usage: atomic [on|off]");
+            return;
+        }
+    }
 
-    let parsed_space = user_num.trim();
+    if *atomic {
+        println!("Push applies the whole line or nothing");
+    } else {
+        println!("Push applies as much of the line as it can");
+    }
+}
 
-    for i in parsed_space.split_whitespace() {
-        let parsed_num: i32 = i.parse().expect("Invalid input");
-        if *head == capacity {
-            println!("Stack is full. Cannot push more elements.");
+fn set_policy(policy: &mut InvalidTokenPolicy, args: &str) {
+    match args {
+        "" => {}
+        "skip" => *policy = InvalidTokenPolicy::Skip,
+        "reject" => *policy = InvalidTokenPolicy::Reject,
+        _ => {
+            println!("Usage: invalid [skip|reject]");
             return;
         }
-        numbers.push(parsed_num);
-        *head += 1;
     }
+
+    match policy {
+        InvalidTokenPolicy::Skip => println!("Invalid tokens are skipped"),
+        InvalidTokenPolicy::Reject => println!("Lines with invalid tokens are rejected"),
+    }
+}
+
+fn set_radix(radix: &mut Radix, args: &str) {
+    let requested = match args {
+        "" => *radix,
+        "2" => Radix::Binary,
+        "8" => Radix::Octal,
+        "10" => Radix::Decimal,
+        "16" => Radix::Hexadecimal,
+        _ => {
+            println!("Usage: base [2|8|10|16]");
+            return;
+        }
+    };
+
+    *radix = requested;
+    println!("Integers are shown in base {}", radix.value());
+}
+
+/// Formats `element` in `radix`, falling back to its `Display` form.
+fn format_element<T: Element>(element: &T, radix: Radix) -> String {
+    element
+        .to_radix_string(radix)
+        .unwrap_or_else(|| element.to_string())
 }
 
-fn pop(numbers: &mut Vec<i32>, head: &mut usize) {
-    if *head == 0 {
-        println!("All elements have been removed from the stack");
+fn push<T: Element + Codec + PartialOrd + Clone>(
+    session: &mut Session<T>,
+    args: &str,
+    settings: &Settings,
+) {
+    if args.is_empty() {
+        println!("Usage: push <v>...");
+        return;
+    }
+
+    if settings.atomic {
+        push_atomic(session, args);
+        return;
+    }
+
+    let parsed = parse_line::<T>(args, settings.invalid);
+
+    for invalid in &parsed.invalid {
+        println!("Error: {} (`{}`)", invalid.error(), invalid.token);
+    }
+
+    if parsed.values.is_empty() {
+        println!("Nothing was pushed. Please enter the line again");
         return;
     }
 
-    *head -= 1;
+    for value in parsed.values {
+        match session.apply(Op::Push(value)) {
+            Ok(Outcome::Element(displaced)) => match session.stack().policy() {
+                OverflowPolicy::OverwriteTop => {
+                    println!(
+                        "Overwrote {} on top of the stack",
+                        format_element(&displaced, settings.radix)
+                    )
+                }
+                _ => println!(
+                    "Evicted {} from the bottom of the stack",
+                    format_element(&displaced, settings.radix)
+                ),
+            },
+            Ok(_) => {}
+            Err(err) => {
+                println!("Error: {}", err);
+                return;
+            }
+        }
+    }
+}
+
+/// Pushes every value on the line, or none of them.
+fn push_atomic<T: Element + Codec + PartialOrd + Clone>(session: &mut Session<T>, args: &str) {
+    let tokens: Vec<&str> = args.split_whitespace().collect();
+    let values = match parse_batch::<T>(args) {
+        Ok(values) => values,
+        Err(err) => return report_rejected(&tokens, &err.rejected),
+    };
 
-    let removed_element = numbers.pop();
+    let count = values.len();
+    match session.push_batch(values) {
+        Ok(rejected) if rejected.is_empty() => println!("Pushed {} element(s)", count),
+        Ok(rejected) => report_rejected(&tokens, &rejected),
+        Err(err) => println!("Error: {}", err),
+    }
+}
 
-    if let Some(element) = removed_element {
-        println!("The removed element from the stack is {}", element);
+fn report_rejected(tokens: &[&str], rejected: &[RejectedToken]) {
+    for rejected in rejected {
+        println!(
+            "Error: `{}`: {}",
+            tokens[rejected.position - 1],
+            rejected.error
+        );
     }
+    println!("Nothing was pushed. Please enter the line again");
 }
 
-fn display(numbers: &[i32], head: usize) {
-    if head == 0 {
+fn pop<T: Element + Codec + PartialOrd + Clone>(
+    session: &mut Session<T>,
+    args: &str,
+    radix: Radix,
+) {
+    let count = if args.is_empty() {
+        1
+    } else {
+        match args.parse::<usize>() {
+            Ok(count) => count,
+            Err(_) => {
+                println!("Usage: pop [n]");
+                return;
+            }
+        }
+    };
+
+    for _ in 0..count {
+        match session.apply(Op::Pop) {
+            Ok(Outcome::Element(element)) => println!(
+                "The removed element from the stack is {}",
+                format_element(&element, radix)
+            ),
+            Ok(_) => {}
+            Err(err) => {
+                println!("Error: {}", err);
+                return;
+            }
+        }
+    }
+}
+
+fn peek<T: Element>(stack: &Stack<T>, radix: Radix) {
+    match stack.peek() {
+        Some(top) => println!("Top of the stack contains {}", format_element(top, radix)),
+        None => println!("The stack is empty"),
+    }
+}
+
+fn stats<T: Element + Codec + PartialOrd + Clone>(
+    session: &Session<T>,
+    command: &str,
+    radix: Radix,
+) {
+    if session.stack().is_empty() {
+        println!("The stack is empty");
+        return;
+    }
+
+    let summary = &session.summary;
+    match command {
+        "min" | "max" => {
+            let extreme = if command == "min" {
+                summary.min()
+            } else {
+                summary.max()
+            };
+            if let Some(extreme) = extreme {
+                println!(
+                    "The {} element is {}",
+                    command,
+                    format_element(extreme, radix)
+                );
+            }
+        }
+        _ => match (summary.sum(), summary.mean()) {
+            (Some(sum), _) if command == "sum" => println!("The sum of the elements is {}", sum),
+            (_, Some(mean)) => println!("The mean of the elements is {}", mean),
+            _ => println!("The elements are not numbers"),
+        },
+    }
+}
+
+fn display<T: Element>(stack: &Stack<T>, radix: Radix) {
+    if stack.is_empty() {
         println!("The stack is empty");
         return;
     }
 
     println!("The elements in the stack are:");
 
-    for i in (0..head).rev() {
-        println!("{}", numbers[i]);
+    for element in stack {
+        println!("{}", format_element(element, radix));
     }
 }
 
-fn top_of_the_stack(numbers: &[i32], head: usize) -> i32 {
-    if head == 0 {
-        println!("The stack is empty");
-        return 0;
+fn history<T: Element>(history: &History<T>, radix: Radix) {
+    if history.is_empty() {
+        println!("Nothing has changed yet");
+        return;
     }
 
-    numbers[head - 1]
+    for event in history.events() {
+        println!(
+            "{:>4}  {}  {}  =>  {}",
+            event.seq,
+            format_time(event),
+            describe_op(&event.op, radix),
+            describe_result(event, radix)
+        );
+    }
+}
+
+fn goto<T: Element + Clone>(history: &History<T>, args: &str, radix: Radix) {
+    let first = history.first_seq() - 1;
+    let last = history.last_seq();
+    let stack = match args
+        .parse::<u64>()
+        .ok()
+        .and_then(|seq| history.replay_to(seq))
+    {
+        Some(stack) => stack,
+        None => {
+            println!("Usage: goto <n>, where n is {} to {}", first, last);
+            return;
+        }
+    };
+
+    println!("As of change {}:", args);
+    display(&stack, radix);
+}
+
+/// Formats the time of `event` as `HH:MM:SS` in UTC.
+fn format_time<T>(event: &Event<T>) -> String {
+    let seconds = event
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn describe_op<T: Element>(op: &Op<T>, radix: Radix) -> String {
+    match op {
+        Op::Push(element) => format!("push {}", format_element(element, radix)),
+        Op::PushBatch(elements) => {
+            let elements: Vec<String> = elements
+                .iter()
+                .map(|element| format_element(element, radix))
+                .collect();
+            format!("push {}", elements.join(" "))
+        }
+        Op::Pop => "pop".to_string(),
+        Op::Clear => "clear".to_string(),
+        Op::Begin => "begin".to_string(),
+        Op::Savepoint => "savepoint".to_string(),
+        Op::RollbackTo(savepoint) => format!("rollback to savepoint #{}", savepoint.id()),
+        Op::Rollback => "rollback".to_string(),
+        Op::Commit => "commit".to_string(),
+        Op::Word(word) => word.to_string(),
+    }
+}
+
+fn describe_result<T: Element>(event: &Event<T>, radix: Radix) -> String {
+    match (&event.op, &event.result) {
+        (Op::Pop, Ok(Outcome::Element(element))) => {
+            format!("removed {}", format_element(element, radix))
+        }
+        (_, Ok(Outcome::Element(element))) => {
+            format!("displaced {}", format_element(element, radix))
+        }
+        (_, Ok(Outcome::Savepoint(savepoint))) => format!("savepoint #{}", savepoint.id()),
+        (_, Ok(Outcome::Done)) => "ok".to_string(),
+        (_, Err(err)) => format!("error: {}", err),
+    }
 }
@@ -0,0 +1,194 @@
+use crate::element::{Element, Total};
+use crate::error::StackError;
+use crate::iter::Iter;
+use crate::op::Op;
+use crate::stack::{OverflowPolicy, Stack};
+
+/// The minimum, maximum and running total of the elements up to and
+/// including one element.
+#[derive(Debug, Clone)]
+struct Frame<T> {
+    min: T,
+    max: T,
+    sum: Option<Total>,
+}
+
+impl<T: Element + PartialOrd + Clone> Frame<T> {
+    /// Makes the frame for `value` pushed on top of `below`.
+    fn above(below: Option<&Frame<T>>, value: &T) -> Self {
+        match below {
+            Some(below) => Frame {
+                min: if *value < below.min {
+                    value.clone()
+                } else {
+                    below.min.clone()
+                },
+                max: if *value > below.max {
+                    value.clone()
+                } else {
+                    below.max.clone()
+                },
+                sum: below
+                    .sum
+                    .zip(value.to_total())
+                    .map(|(sum, value)| sum + value),
+            },
+            None => Frame {
+                min: value.clone(),
+                max: value.clone(),
+                sum: value.to_total(),
+            },
+        }
+    }
+}
+
+/// A stack that answers `min`, `max`, `sum` and `mean` in O(1).
+///
+/// It keeps a [`Summary`] of its elements in step with every push and pop,
+/// so popping simply reveals the summary of the elements below.
+#[derive(Debug, Clone)]
+pub struct MinMaxStack<T> {
+    elements: Stack<T>,
+    summary: Summary<T>,
+}
+
+impl<T: Element + PartialOrd + Clone> MinMaxStack<T> {
+    /// Creates an empty stack that can hold up to `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let elements = Stack::with_capacity(capacity);
+        let summary = Summary::new(&elements);
+        MinMaxStack { elements, summary }
+    }
+
+    /// Fails with [`StackError::Overflow`] when the stack is full.
+    pub fn push(&mut self, value: T) -> Result<(), StackError> {
+        let unchanged = self.elements.len();
+        self.elements.push(value)?;
+        self.summary.sync(&self.elements, unchanged);
+        Ok(())
+    }
+
+    /// Fails with [`StackError::Underflow`] when the stack is empty.
+    pub fn pop(&mut self) -> Result<T, StackError> {
+        let value = self.elements.pop()?;
+        self.summary.sync(&self.elements, self.elements.len());
+        Ok(value)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.elements.peek()
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.summary.min()
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.summary.max()
+    }
+
+    /// Returns the total of every element, or `None` if the stack is empty
+    /// or its elements are not numeric.
+    pub fn sum(&self) -> Option<Total> {
+        self.summary.sum()
+    }
+
+    /// Returns the average of every element, or `None` like [`MinMaxStack::sum`].
+    pub fn mean(&self) -> Option<f64> {
+        self.summary.mean()
+    }
+
+    pub fn clear(&mut self) {
+        self.elements.clear();
+        self.summary.sync(&self.elements, 0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.elements.capacity()
+    }
+
+    /// Iterates over the elements from top to bottom.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.elements.iter()
+    }
+}
+
+/// The `min`, `max`, `sum` and `mean` of a [`Stack`] the caller owns, kept
+/// up to date as ops are applied to it so each answer is O(1).
+///
+/// It keeps a frame per element summarizing the elements up to it. An op
+/// only invalidates the frames of the elements it can change, which
+/// [`Summary::unchanged_by`] works out before the op is applied and
+/// [`Summary::sync`] rebuilds afterwards.
+#[derive(Debug, Clone)]
+pub struct Summary<T> {
+    frames: Vec<Frame<T>>,
+}
+
+impl<T: Element + PartialOrd + Clone> Summary<T> {
+    /// Summarizes every element of `stack`.
+    pub fn new(stack: &Stack<T>) -> Self {
+        let mut summary = Summary { frames: Vec::new() };
+        summary.sync(stack, 0);
+        summary
+    }
+
+    /// Returns how many elements at the bottom of `stack` applying `op` is
+    /// sure to leave as they are.
+    pub fn unchanged_by(stack: &Stack<T>, op: &Op<T>) -> usize {
+        let len = stack.len();
+        let pushed = match op {
+            Op::Push(_) => 1,
+            Op::PushBatch(elements) => elements.len(),
+            Op::Pop | Op::Clear | Op::Begin | Op::Savepoint | Op::Commit => return len,
+            Op::RollbackTo(_) | Op::Rollback => return 0,
+            Op::Word(word) => return len.saturating_sub(word.takes()),
+        };
+
+        match stack.policy() {
+            OverflowPolicy::Reject | OverflowPolicy::Grow => len,
+            OverflowPolicy::EvictBottom if len + pushed > stack.capacity() => 0,
+            OverflowPolicy::EvictBottom => len,
+            OverflowPolicy::OverwriteTop => len.saturating_sub(1),
+        }
+    }
+
+    /// Catches up with `stack`, whose bottom `unchanged` elements are the
+    /// ones summarized before.
+    pub fn sync(&mut self, stack: &Stack<T>, unchanged: usize) {
+        let kept = unchanged.min(stack.len()).min(self.frames.len());
+        self.frames.truncate(kept);
+        for value in stack.iter_bottom_up().skip(kept) {
+            let frame = Frame::above(self.frames.last(), value);
+            self.frames.push(frame);
+        }
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.frames.last().map(|frame| &frame.min)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.frames.last().map(|frame| &frame.max)
+    }
+
+    /// Returns the total of every element, or `None` if the stack is empty
+    /// or its elements are not numeric.
+    pub fn sum(&self) -> Option<Total> {
+        self.frames.last().and_then(|frame| frame.sum)
+    }
+
+    /// Returns the average of every element, or `None` like [`Summary::sum`].
+    pub fn mean(&self) -> Option<f64> {
+        self.sum()
+            .map(|sum| sum.to_f64() / self.frames.len() as f64)
+    }
+}
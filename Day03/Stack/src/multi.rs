@@ -0,0 +1,212 @@
+use std::fmt;
+use std::iter::Rev;
+use std::mem::MaybeUninit;
+use std::{ptr, slice};
+
+use crate::error::StackError;
+
+/// Several stacks sharing one fixed-size buffer.
+///
+/// Each stack owns a region of the buffer, starting out with an equal share.
+/// When a stack fills its region, it takes free slots from the nearest
+/// region that has some, shifting the regions in between. A push only fails
+/// when the whole buffer is full.
+///
+/// Stacks are numbered from 0; every method taking a stack number panics if
+/// it is not below [`MultiStack::stack_count`].
+pub struct MultiStack<T> {
+    buffer: Box<[MaybeUninit<T>]>,
+    /// Stack `i` owns `bases[i]..bases[i + 1]`; the last entry is the
+    /// capacity.
+    bases: Vec<usize>,
+    /// Stack `i` holds its elements in `bases[i]..tops[i]`, and only those
+    /// slots are initialized.
+    tops: Vec<usize>,
+}
+
+impl<T> MultiStack<T> {
+    /// Creates `stacks` empty stacks sharing room for `capacity` elements.
+    ///
+    /// Panics if `stacks` is zero.
+    pub fn new(stacks: usize, capacity: usize) -> Self {
+        assert!(stacks > 0, "a multi-stack needs at least one stack");
+
+        let bases: Vec<usize> = (0..=stacks).map(|i| i * capacity / stacks).collect();
+        MultiStack {
+            buffer: (0..capacity).map(|_| MaybeUninit::uninit()).collect(),
+            tops: bases[..stacks].to_vec(),
+            bases,
+        }
+    }
+
+    /// Pushes `element` on top of stack `stack`.
+    ///
+    /// Fails with [`StackError::Overflow`] when the whole buffer is full.
+    pub fn push(&mut self, stack: usize, element: T) -> Result<(), StackError> {
+        self.check(stack);
+        if self.tops[stack] == self.bases[stack + 1] {
+            self.make_room(stack)?;
+        }
+
+        self.buffer[self.tops[stack]].write(element);
+        self.tops[stack] += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the top element of stack `stack`.
+    ///
+    /// Fails with [`StackError::Underflow`] when that stack is empty.
+    pub fn pop(&mut self, stack: usize) -> Result<T, StackError> {
+        self.check(stack);
+        if self.tops[stack] == self.bases[stack] {
+            return Err(StackError::Underflow);
+        }
+
+        self.tops[stack] -= 1;
+        // SAFETY: the slot was below the top, so it is initialized, and it
+        // is now above it, so it will not be read or dropped again.
+        Ok(unsafe { self.buffer[self.tops[stack]].assume_init_read() })
+    }
+
+    /// Returns a reference to the top element of stack `stack`.
+    pub fn peek(&self, stack: usize) -> Option<&T> {
+        self.as_slice(stack).last()
+    }
+
+    /// Removes every element of stack `stack`, leaving its region to it.
+    pub fn clear(&mut self, stack: usize) {
+        let initialized: *mut [T] = self.as_mut_slice(stack);
+        // Forget the elements first, so a panicking `drop` cannot lead to
+        // them being dropped twice.
+        self.tops[stack] = self.bases[stack];
+        // SAFETY: the slice covered exactly the initialized elements.
+        unsafe { ptr::drop_in_place(initialized) };
+    }
+
+    /// Returns the number of elements in stack `stack`.
+    pub fn len(&self, stack: usize) -> usize {
+        self.check(stack);
+        self.tops[stack] - self.bases[stack]
+    }
+
+    pub fn is_empty(&self, stack: usize) -> bool {
+        self.len(stack) == 0
+    }
+
+    /// Returns the number of elements in all the stacks together.
+    pub fn total_len(&self) -> usize {
+        (0..self.stack_count()).map(|stack| self.len(stack)).sum()
+    }
+
+    /// Returns whether every slot of the buffer is in use.
+    pub fn is_full(&self) -> bool {
+        self.total_len() == self.capacity()
+    }
+
+    /// Returns the number of elements all the stacks can hold together.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn stack_count(&self) -> usize {
+        self.tops.len()
+    }
+
+    /// Returns the elements of stack `stack` from bottom to top.
+    pub fn as_slice(&self, stack: usize) -> &[T] {
+        self.check(stack);
+        let start = self.bases[stack];
+        let len = self.tops[stack] - start;
+        // SAFETY: the slots from the base to the top are initialized, and
+        // `MaybeUninit<T>` has the same layout as `T`.
+        unsafe { slice::from_raw_parts(self.buffer.as_ptr().add(start).cast::<T>(), len) }
+    }
+
+    fn as_mut_slice(&mut self, stack: usize) -> &mut [T] {
+        self.check(stack);
+        let start = self.bases[stack];
+        let len = self.tops[stack] - start;
+        // SAFETY: as in `as_slice`.
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_mut_ptr().add(start).cast::<T>(), len) }
+    }
+
+    /// Iterates over the elements of stack `stack` from top to bottom.
+    pub fn iter(&self, stack: usize) -> Rev<slice::Iter<'_, T>> {
+        self.as_slice(stack).iter().rev()
+    }
+
+    fn check(&self, stack: usize) {
+        assert!(
+            stack < self.stack_count(),
+            "stack {} does not exist, there are {}",
+            stack,
+            self.stack_count()
+        );
+    }
+
+    /// Grows the full region of `stack` by taking half of the free slots of
+    /// the nearest region that has any, above it if possible.
+    fn make_room(&mut self, stack: usize) -> Result<(), StackError> {
+        let count = self.stack_count();
+        let free = |this: &Self, i: usize| this.bases[i + 1] - this.tops[i];
+
+        if let Some(donor) = (stack + 1..count).find(|&i| free(self, i) > 0) {
+            // Regions `stack + 1..donor` are full, so everything from the
+            // start of the next region to the donor's top is initialized.
+            let shift = free(self, donor).div_ceil(2);
+            let start = self.bases[stack + 1];
+            let end = self.tops[donor];
+            // SAFETY: the destination ends at `end + shift`, within the
+            // donor's free slots, and `ptr::copy` allows the overlap. The
+            // slots left behind become the free space of `stack`.
+            unsafe {
+                let base = self.buffer.as_mut_ptr();
+                ptr::copy(base.add(start), base.add(start + shift), end - start);
+            }
+            for i in stack + 1..=donor {
+                self.bases[i] += shift;
+                self.tops[i] += shift;
+            }
+            return Ok(());
+        }
+
+        if let Some(donor) = (0..stack).rev().find(|&i| free(self, i) > 0) {
+            // Regions `donor + 1..=stack` are full, so everything from the
+            // end of the donor's region to the top of `stack` is initialized.
+            let shift = free(self, donor).div_ceil(2);
+            let start = self.bases[donor + 1];
+            let end = self.tops[stack];
+            // SAFETY: the destination starts at `start - shift`, within the
+            // donor's free slots, and `ptr::copy` allows the overlap.
+            unsafe {
+                let base = self.buffer.as_mut_ptr();
+                ptr::copy(base.add(start), base.add(start - shift), end - start);
+            }
+            for i in donor + 1..=stack {
+                self.bases[i] -= shift;
+                self.tops[i] -= shift;
+            }
+            return Ok(());
+        }
+
+        Err(StackError::Overflow {
+            capacity: self.capacity(),
+        })
+    }
+}
+
+impl<T> Drop for MultiStack<T> {
+    fn drop(&mut self) {
+        for stack in 0..self.stack_count() {
+            self.clear(stack);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MultiStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.stack_count()).map(|stack| self.as_slice(stack)))
+            .finish()
+    }
+}
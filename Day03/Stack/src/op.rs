@@ -0,0 +1,35 @@
+use crate::forth::Word;
+use crate::transaction::Savepoint;
+
+/// A mutation of a [`Stack`], in a form that can be logged and replayed
+/// with [`Stack::apply`].
+///
+/// [`Stack`]: crate::Stack
+/// [`Stack::apply`]: crate::Stack::apply
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op<T> {
+    Push(T),
+    /// Pushes every element or none of them, like [`Stack::push_batch`].
+    ///
+    /// [`Stack::push_batch`]: crate::Stack::push_batch
+    PushBatch(Vec<T>),
+    Pop,
+    Clear,
+    Begin,
+    Savepoint,
+    RollbackTo(Savepoint),
+    Rollback,
+    Commit,
+    Word(Word),
+}
+
+/// What applying an [`Op`] produced.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome<T> {
+    /// The op left nothing to hand back.
+    Done,
+    /// The element popped, or displaced by a push on a full stack.
+    Element(T),
+    /// The savepoint created by [`Op::Savepoint`].
+    Savepoint(Savepoint),
+}
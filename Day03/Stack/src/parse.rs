@@ -0,0 +1,84 @@
+use crate::element::Element;
+use crate::error::{BatchError, InputErrorKind, RejectedToken, StackError};
+
+/// What to do with a line that contains tokens which fail to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidTokenPolicy {
+    /// Keep the tokens that parse and drop the rest.
+    Skip,
+    /// Keep nothing if any token fails to parse.
+    Reject,
+}
+
+/// A token that could not be parsed, with its 1-based position in the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidToken<'a> {
+    pub position: usize,
+    pub token: &'a str,
+    pub kind: InputErrorKind,
+}
+
+impl InvalidToken<'_> {
+    pub fn error(&self) -> StackError {
+        StackError::InvalidInput {
+            position: self.position,
+            kind: self.kind,
+        }
+    }
+}
+
+/// The outcome of parsing a line under an [`InvalidTokenPolicy`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedLine<'a, T> {
+    pub values: Vec<T>,
+    pub invalid: Vec<InvalidToken<'a>>,
+}
+
+/// Parses every whitespace-separated token of `line`, collecting all the invalid ones.
+///
+/// With [`InvalidTokenPolicy::Reject`], `values` is empty whenever `invalid` is not.
+pub fn parse_line<T: Element>(line: &str, policy: InvalidTokenPolicy) -> ParsedLine<'_, T> {
+    let mut values = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (index, token) in line.split_whitespace().enumerate() {
+        match T::parse_token(token) {
+            Ok(value) => values.push(value),
+            Err(kind) => invalid.push(InvalidToken {
+                position: index + 1,
+                token,
+                kind,
+            }),
+        }
+    }
+
+    if policy == InvalidTokenPolicy::Reject && !invalid.is_empty() {
+        values.clear();
+    }
+
+    ParsedLine { values, invalid }
+}
+
+/// Parses every whitespace-separated token of `line`, or reports every one
+/// that fails.
+///
+/// The rejected tokens carry the same 1-based positions as a
+/// [`Stack::push_batch`](crate::Stack::push_batch) overflow, so a caller can
+/// report both kinds of rejection the same way.
+pub fn parse_batch<T: Element>(line: &str) -> Result<Vec<T>, BatchError> {
+    let parsed = parse_line::<T>(line, InvalidTokenPolicy::Reject);
+
+    if !parsed.invalid.is_empty() {
+        let rejected = parsed
+            .invalid
+            .iter()
+            .map(|invalid| RejectedToken {
+                position: invalid.position,
+                error: invalid.error(),
+            })
+            .collect();
+        return Err(BatchError { rejected });
+    }
+
+    Ok(parsed.values)
+}
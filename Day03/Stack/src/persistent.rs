@@ -0,0 +1,151 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+use crate::error::StackError;
+
+type Link<T> = Option<Rc<Cons<T>>>;
+
+/// One cell of the cons list: a value and the rest of the stack below it.
+struct Cons<T> {
+    value: T,
+    next: Link<T>,
+}
+
+/// An immutable stack whose versions share their common cells.
+///
+/// `push` and `pop` leave `self` untouched and return a new version in O(1),
+/// so every earlier version stays valid and cloning one is just a reference
+/// count increment.
+pub struct PersistentStack<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> PersistentStack<T> {
+    pub fn new() -> Self {
+        PersistentStack { head: None, len: 0 }
+    }
+
+    /// Returns a new version with `value` on top of this one.
+    pub fn push(&self, value: T) -> Self {
+        PersistentStack {
+            head: Some(Rc::new(Cons {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    /// Returns the top element together with the version below it.
+    ///
+    /// Fails with [`StackError::Underflow`] when the stack is empty.
+    pub fn pop(&self) -> Result<(&T, Self), StackError> {
+        let cell = self.head.as_ref().ok_or(StackError::Underflow)?;
+        let rest = PersistentStack {
+            head: cell.next.clone(),
+            len: self.len - 1,
+        };
+        Ok((&cell.value, rest))
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_ref().map(|cell| &cell.value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns `true` if both versions are the very same cells.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Iterates over the elements from top to bottom.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Clone for PersistentStack<T> {
+    fn clone(&self) -> Self {
+        PersistentStack {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for PersistentStack<T> {
+    fn default() -> Self {
+        PersistentStack::new()
+    }
+}
+
+/// Unlinks cells one at a time, so dropping a deep stack cannot overflow
+/// the call stack. Cells still shared with another version are left alone.
+impl<T> Drop for PersistentStack<T> {
+    fn drop(&mut self) {
+        let mut link = self.head.take();
+        while let Some(cell) = link {
+            match Rc::try_unwrap(cell) {
+                Ok(mut cell) => link = cell.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Builds a stack with the last element of the iterator on top.
+impl<T> FromIterator<T> for PersistentStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(elements: I) -> Self {
+        let mut stack = PersistentStack::new();
+        for element in elements {
+            stack = stack.push(element);
+        }
+        stack
+    }
+}
+
+/// Borrowing iterator over a [`PersistentStack`] from top to bottom.
+pub struct Iter<'a, T> {
+    next: Option<&'a Cons<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|cell| {
+            self.next = cell.next.as_deref();
+            &cell.value
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentStack<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
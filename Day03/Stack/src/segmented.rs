@@ -0,0 +1,121 @@
+use crate::error::StackError;
+
+/// Number of elements per segment used by [`SegmentedStack::new`].
+pub const DEFAULT_SEGMENT_LEN: usize = 1024;
+
+/// An unbounded stack made of fixed-size segments.
+///
+/// Growing never moves existing elements: a full segment is left where it
+/// is and a new one is started on top of it. When popping empties the top
+/// segment, it is kept as a spare instead of being freed, so pushing and
+/// popping across a segment boundary does not allocate each time.
+#[derive(Debug, Clone)]
+pub struct SegmentedStack<T> {
+    segments: Vec<Vec<T>>,
+    spare: Option<Vec<T>>,
+    segment_len: usize,
+    len: usize,
+}
+
+impl<T> SegmentedStack<T> {
+    pub fn new() -> Self {
+        SegmentedStack::with_segment_len(DEFAULT_SEGMENT_LEN)
+    }
+
+    /// Creates an empty stack whose segments hold `segment_len` elements each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_len` is zero.
+    pub fn with_segment_len(segment_len: usize) -> Self {
+        assert!(segment_len > 0, "segments must hold at least one element");
+
+        SegmentedStack {
+            segments: Vec::new(),
+            spare: None,
+            segment_len,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, element: T) {
+        let top_is_full = self
+            .segments
+            .last()
+            .is_none_or(|top| top.len() == self.segment_len);
+
+        if top_is_full {
+            let segment = self
+                .spare
+                .take()
+                .unwrap_or_else(|| Vec::with_capacity(self.segment_len));
+            self.segments.push(segment);
+        }
+
+        if let Some(top) = self.segments.last_mut() {
+            top.push(element);
+            self.len += 1;
+        }
+    }
+
+    /// Removes and returns the top element.
+    ///
+    /// Fails with [`StackError::Underflow`] when the stack is empty.
+    pub fn pop(&mut self) -> Result<T, StackError> {
+        let top = self.segments.last_mut().ok_or(StackError::Underflow)?;
+        let element = top.pop().ok_or(StackError::Underflow)?;
+        self.len -= 1;
+
+        if top.is_empty() {
+            // Keep one empty segment around; any other is freed here.
+            self.spare = self.segments.pop();
+        }
+
+        Ok(element)
+    }
+
+    /// Returns a reference to the top element without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.segments.last().and_then(|top| top.last())
+    }
+
+    /// Removes every element, keeping one segment as the spare.
+    pub fn clear(&mut self) {
+        if let Some(mut segment) = self.segments.drain(..).next() {
+            segment.clear();
+            self.spare = Some(segment);
+        }
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn segment_len(&self) -> usize {
+        self.segment_len
+    }
+
+    /// Returns the number of segments in use, not counting the spare.
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Iterates over the elements from top to bottom.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.segments
+            .iter()
+            .rev()
+            .flat_map(|segment| segment.iter().rev())
+    }
+}
+
+impl<T> Default for SegmentedStack<T> {
+    fn default() -> Self {
+        SegmentedStack::new()
+    }
+}
@@ -0,0 +1,421 @@
+//! Saving a [`Stack`] to a file and loading it back.
+//!
+//! Two formats are supported, and [`load`] tells them apart by their first
+//! bytes. Both record the element type, overflow policy, capacity and the
+//! elements from bottom to top, and both end in a CRC-32 checksum
+//! (IEEE polynomial) of everything before it.
+//!
+//! The binary format, with every number little-endian:
+//!
+//! | Field    | Size     | Contents                                      |
+//! |----------|----------|-----------------------------------------------|
+//! | magic    | 4 bytes  | `STKS`                                        |
+//! | version  | `u16`    | `1`                                           |
+//! | type     | `u8`     | [`Codec::TYPE_TAG`] of the element type       |
+//! | policy   | `u8`     | index of the policy in [`OverflowPolicy::ALL`]|
+//! | capacity | `u64`    |                                               |
+//! | count    | `u64`    | number of elements                            |
+//! | elements | variable | each element encoded with [`Codec`]           |
+//! | checksum | `u32`    | CRC-32 of all the bytes above                 |
+//!
+//! The text format has one field per line:
+//!
+//! ```text
+//! stack snapshot v1
+//! type i32
+//! policy reject
+//! capacity 10
+//! count 2
+//! 1
+//! 2
+//! checksum 2f18c7b5
+//! ```
+//!
+//! Each element line holds its `Display` form with `\` written as `\\`,
+//! newlines as `\n` and carriage returns as `\r`. The checksum is written
+//! in hexadecimal and covers every line before it, newlines included.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::codec::{self, Codec};
+use crate::element::Element;
+use crate::stack::{OverflowPolicy, Stack};
+
+/// The first bytes of a binary snapshot.
+pub const MAGIC: [u8; 4] = *b"STKS";
+/// The newest format version this crate reads and the one it writes.
+pub const VERSION: u16 = 1;
+
+const TEXT_HEADER: &str = "stack snapshot v";
+
+/// Which snapshot format to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Binary,
+    Text,
+}
+
+/// Errors returned when saving or loading a snapshot.
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    /// The file starts with neither the binary nor the text header.
+    UnknownFormat,
+    /// The file was written by a newer version of the format.
+    UnsupportedVersion(u16),
+    /// The file holds elements of a different type.
+    TypeMismatch {
+        expected: &'static str,
+        found: String,
+    },
+    /// The checksum does not match the contents.
+    ChecksumMismatch,
+    /// The checksum matched but the contents make no sense.
+    Corrupt(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => err.fmt(f),
+            SnapshotError::UnknownFormat => write!(f, "not a stack snapshot"),
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot version {} is newer than the supported version {}",
+                version, VERSION
+            ),
+            SnapshotError::TypeMismatch { expected, found } => write!(
+                f,
+                "snapshot holds {} elements, expected {}",
+                found, expected
+            ),
+            SnapshotError::ChecksumMismatch => write!(f, "snapshot checksum does not match"),
+            SnapshotError::Corrupt(reason) => write!(f, "snapshot is corrupted: {}", reason),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+fn corrupt(reason: impl Into<String>) -> SnapshotError {
+    SnapshotError::Corrupt(reason.into())
+}
+
+/// Computes the CRC-32 (IEEE) checksum of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Writes `stack` in the binary format.
+pub fn write_binary<T: Codec, W: Write>(
+    stack: &Stack<T>,
+    mut writer: W,
+) -> Result<(), SnapshotError> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&MAGIC);
+    VERSION.encode(&mut bytes);
+    T::TYPE_TAG.encode(&mut bytes);
+    policy_index(stack.policy()).encode(&mut bytes);
+    (stack.capacity() as u64).encode(&mut bytes);
+    (stack.len() as u64).encode(&mut bytes);
+    for element in stack.iter_bottom_up() {
+        element.encode(&mut bytes);
+    }
+    crc32(&bytes).encode(&mut bytes);
+
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Reads a stack written by [`write_binary`].
+pub fn read_binary<T: Codec, R: Read>(mut reader: R) -> Result<Stack<T>, SnapshotError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    decode_binary(&bytes)
+}
+
+fn decode_binary<T: Codec>(bytes: &[u8]) -> Result<Stack<T>, SnapshotError> {
+    if !bytes.starts_with(&MAGIC) {
+        return Err(SnapshotError::UnknownFormat);
+    }
+
+    let mut input = &bytes[MAGIC.len()..];
+    let version = u16::decode(&mut input).ok_or_else(|| corrupt("missing version"))?;
+    if version > VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    if bytes.len() < MAGIC.len() + 4 {
+        return Err(corrupt("missing checksum"));
+    }
+    let (contents, mut checksum) = bytes.split_at(bytes.len() - 4);
+    if u32::decode(&mut checksum) != Some(crc32(contents)) {
+        return Err(SnapshotError::ChecksumMismatch);
+    }
+    let mut input = &contents[MAGIC.len() + 2..];
+
+    let tag = u8::decode(&mut input).ok_or_else(|| corrupt("missing element type"))?;
+    if tag != T::TYPE_TAG {
+        return Err(SnapshotError::TypeMismatch {
+            expected: T::TYPE_NAME,
+            found: codec::type_name(tag)
+                .map_or_else(|| format!("unknown type {}", tag), String::from),
+        });
+    }
+
+    let policy = u8::decode(&mut input)
+        .and_then(|index| OverflowPolicy::ALL.get(usize::from(index)).copied())
+        .ok_or_else(|| corrupt("unknown overflow policy"))?;
+    let capacity = u64::decode(&mut input)
+        .and_then(|capacity| usize::try_from(capacity).ok())
+        .ok_or_else(|| corrupt("invalid capacity"))?;
+    let count = u64::decode(&mut input)
+        .and_then(|count| usize::try_from(count).ok())
+        .ok_or_else(|| corrupt("invalid element count"))?;
+
+    let mut elements = Vec::new();
+    for position in 1..=count {
+        let element = T::decode(&mut input)
+            .ok_or_else(|| corrupt(format!("element {} does not decode", position)))?;
+        elements.push(element);
+    }
+    if !input.is_empty() {
+        return Err(corrupt("trailing bytes after the elements"));
+    }
+
+    build(capacity, policy, elements)
+}
+
+/// Writes `stack` in the text format.
+pub fn write_text<T: Element + Codec, W: Write>(
+    stack: &Stack<T>,
+    mut writer: W,
+) -> Result<(), SnapshotError> {
+    let mut text = format!(
+        "{}{}\ntype {}\npolicy {}\ncapacity {}\ncount {}\n",
+        TEXT_HEADER,
+        VERSION,
+        T::TYPE_NAME,
+        stack.policy().name(),
+        stack.capacity(),
+        stack.len()
+    );
+    for element in stack.iter_bottom_up() {
+        text.push_str(&escape(&element.to_string()));
+        text.push('\n');
+    }
+    let checksum = crc32(text.as_bytes());
+    text.push_str(&format!("checksum {:08x}\n", checksum));
+
+    writer.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// Reads a stack written by [`write_text`].
+pub fn read_text<T: Element + Codec, R: Read>(mut reader: R) -> Result<Stack<T>, SnapshotError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    decode_text(&bytes)
+}
+
+fn decode_text<T: Element + Codec>(bytes: &[u8]) -> Result<Stack<T>, SnapshotError> {
+    let text = std::str::from_utf8(bytes).map_err(|_| SnapshotError::UnknownFormat)?;
+    let version = text
+        .strip_prefix(TEXT_HEADER)
+        .and_then(|rest| rest.lines().next())
+        .ok_or(SnapshotError::UnknownFormat)?;
+    let version: u16 = version.parse().map_err(|_| SnapshotError::UnknownFormat)?;
+    if version > VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let body = text
+        .strip_suffix('\n')
+        .ok_or_else(|| corrupt("missing checksum"))?;
+    let checksum_start = body.rfind('\n').map_or(0, |newline| newline + 1);
+    let checksum = body[checksum_start..]
+        .strip_prefix("checksum ")
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| corrupt("missing checksum"))?;
+    let contents = &text[..checksum_start];
+    if checksum != crc32(contents.as_bytes()) {
+        return Err(SnapshotError::ChecksumMismatch);
+    }
+
+    let mut lines = contents.lines().skip(1);
+    let mut field = |name: &str| {
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix(name))
+            .and_then(|line| line.strip_prefix(' '))
+            .ok_or_else(|| corrupt(format!("missing `{}` line", name)))
+    };
+
+    let type_name = field("type")?;
+    if type_name != T::TYPE_NAME {
+        return Err(SnapshotError::TypeMismatch {
+            expected: T::TYPE_NAME,
+            found: type_name.to_string(),
+        });
+    }
+    let policy = OverflowPolicy::from_name(field("policy")?)
+        .ok_or_else(|| corrupt("unknown overflow policy"))?;
+    let capacity = field("capacity")?
+        .parse()
+        .map_err(|_| corrupt("invalid capacity"))?;
+    let count: usize = field("count")?
+        .parse()
+        .map_err(|_| corrupt("invalid element count"))?;
+
+    let mut elements = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let element = unescape(line)
+            .and_then(|line| T::from_str(&line).ok())
+            .ok_or_else(|| corrupt(format!("element {} does not parse", index + 1)))?;
+        elements.push(element);
+    }
+    if elements.len() != count {
+        return Err(corrupt(format!(
+            "expected {} elements, found {}",
+            count,
+            elements.len()
+        )));
+    }
+
+    build(capacity, policy, elements)
+}
+
+/// Writes `stack` to `path` in `format`.
+///
+/// The snapshot is written to a temporary file next to `path` and renamed
+/// over it once complete, so an interrupted save never leaves a torn file.
+pub fn save<T: Element + Codec>(
+    stack: &Stack<T>,
+    path: impl AsRef<Path>,
+    format: Format,
+) -> Result<(), SnapshotError> {
+    write_atomically(path.as_ref(), |file| match format {
+        Format::Binary => write_binary(stack, file),
+        Format::Text => write_text(stack, file),
+    })
+}
+
+/// Writes a file at `path` with `write`, through a temporary file that is
+/// synced and then renamed over `path`.
+pub(crate) fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<(), SnapshotError>,
+) -> Result<(), SnapshotError> {
+    let temporary = temporary_path(path);
+
+    let result = File::create(&temporary)
+        .map_err(SnapshotError::from)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()?;
+            Ok(())
+        })
+        .and_then(|_| fs::rename(&temporary, path).map_err(SnapshotError::from));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+/// Reads a stack from `path`, in whichever format it was saved.
+pub fn load<T: Element + Codec>(path: impl AsRef<Path>) -> Result<Stack<T>, SnapshotError> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(&MAGIC) {
+        decode_binary(&bytes)
+    } else {
+        decode_text(&bytes)
+    }
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+fn policy_index(policy: OverflowPolicy) -> u8 {
+    OverflowPolicy::ALL
+        .iter()
+        .position(|&candidate| candidate == policy)
+        .unwrap_or(0) as u8
+}
+
+fn build<T>(
+    capacity: usize,
+    policy: OverflowPolicy,
+    elements: Vec<T>,
+) -> Result<Stack<T>, SnapshotError> {
+    if elements.len() > capacity {
+        return Err(corrupt("more elements than the capacity"));
+    }
+
+    let mut stack = Stack::with_policy(capacity, policy);
+    stack
+        .push_batch(elements)
+        .map_err(|_| corrupt("more elements than the capacity"))?;
+    Ok(stack)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
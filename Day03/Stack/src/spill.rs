@@ -0,0 +1,272 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::codec::Codec;
+use crate::error::StackError;
+
+/// Errors returned by [`SpillStack`] operations.
+#[derive(Debug)]
+pub enum SpillError {
+    Stack(StackError),
+    Io(io::Error),
+    /// A page read back from the spill file did not decode.
+    Corrupt,
+}
+
+impl fmt::Display for SpillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpillError::Stack(err) => err.fmt(f),
+            SpillError::Io(err) => write!(f, "spill file error: {}", err),
+            SpillError::Corrupt => write!(f, "spill file is corrupted"),
+        }
+    }
+}
+
+impl Error for SpillError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpillError::Stack(err) => Some(err),
+            SpillError::Io(err) => Some(err),
+            SpillError::Corrupt => None,
+        }
+    }
+}
+
+impl From<StackError> for SpillError {
+    fn from(err: StackError) -> Self {
+        SpillError::Stack(err)
+    }
+}
+
+impl From<io::Error> for SpillError {
+    fn from(err: io::Error) -> Self {
+        SpillError::Io(err)
+    }
+}
+
+/// Where a spilled page lives in the spill file.
+#[derive(Debug, Clone, Copy)]
+struct Page {
+    offset: u64,
+    bytes: usize,
+    count: usize,
+}
+
+/// The temporary file holding spilled pages, removed when dropped.
+#[derive(Debug)]
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl SpillFile {
+    fn create(dir: &Path) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let path = dir.join(format!(
+            "stack-spill-{}-{}.tmp",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+
+        Ok(SpillFile { file, path })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A stack that keeps only its top elements in memory and spills older ones
+/// to a temporary file.
+///
+/// At most `max_resident` elements are held in memory. When a push goes
+/// past that, the bottom `page_len` of them are written to the end of the
+/// spill file as one page. Once a pop takes the last element in memory, the
+/// newest page is read back and cut off the file, so the file itself
+/// behaves as a stack of pages and the top is always in memory.
+/// Half of the resident elements are spilled at a time, so alternating
+/// pushes and pops at the boundary do not touch the disk on every call.
+#[derive(Debug)]
+pub struct SpillStack<T> {
+    resident: VecDeque<T>,
+    max_resident: usize,
+    page_len: usize,
+    pages: Vec<Page>,
+    spilled: usize,
+    capacity: usize,
+    dir: PathBuf,
+    file: Option<SpillFile>,
+}
+
+impl<T: Codec> SpillStack<T> {
+    /// Creates an empty stack of up to `capacity` elements that keeps at
+    /// most `max_resident` of them in memory, spilling into the system's
+    /// temporary directory.
+    pub fn new(capacity: usize, max_resident: usize) -> Self {
+        SpillStack::new_in(std::env::temp_dir(), capacity, max_resident)
+    }
+
+    /// Like [`SpillStack::new`], but spills into a file inside `dir`.
+    ///
+    /// The file is only created once the first page is spilled.
+    pub fn new_in<P: Into<PathBuf>>(dir: P, capacity: usize, max_resident: usize) -> Self {
+        let max_resident = max_resident.max(1);
+
+        SpillStack {
+            resident: VecDeque::new(),
+            max_resident,
+            page_len: (max_resident / 2).max(1),
+            pages: Vec::new(),
+            spilled: 0,
+            capacity,
+            dir: dir.into(),
+            file: None,
+        }
+    }
+
+    /// Pushes `element` on top of the stack, spilling a page if memory is full.
+    ///
+    /// Fails with [`StackError::Overflow`] when the stack is full.
+    pub fn push(&mut self, element: T) -> Result<(), SpillError> {
+        if self.len() == self.capacity {
+            return Err(StackError::Overflow {
+                capacity: self.capacity,
+            }
+            .into());
+        }
+
+        if self.resident.len() == self.max_resident {
+            self.spill_page()?;
+        }
+
+        self.resident.push_back(element);
+        Ok(())
+    }
+
+    /// Removes and returns the top element, then reloads a page if it was
+    /// the last element in memory.
+    ///
+    /// Fails with [`StackError::Underflow`] when the stack is empty. If the
+    /// page cannot be reloaded, the element stays on the stack.
+    pub fn pop(&mut self) -> Result<T, SpillError> {
+        let element = self
+            .resident
+            .pop_back()
+            .ok_or(SpillError::Stack(StackError::Underflow))?;
+
+        if self.resident.is_empty() {
+            if let Err(err) = self.reload_page() {
+                self.resident.push_back(element);
+                return Err(err);
+            }
+        }
+
+        Ok(element)
+    }
+
+    /// Returns a reference to the top element without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.resident.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.resident.len() + self.spilled
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns how many elements are held in memory.
+    pub fn resident_len(&self) -> usize {
+        self.resident.len()
+    }
+
+    /// Returns how many elements are in the spill file.
+    pub fn spilled_len(&self) -> usize {
+        self.spilled
+    }
+
+    /// Removes every element and empties the spill file.
+    pub fn clear(&mut self) -> Result<(), SpillError> {
+        self.resident.clear();
+        self.pages.clear();
+        self.spilled = 0;
+        if let Some(spill) = &self.file {
+            spill.file.set_len(0)?;
+        }
+        Ok(())
+    }
+
+    fn spill_page(&mut self) -> Result<(), SpillError> {
+        let count = self.page_len.min(self.resident.len());
+        let mut bytes = Vec::new();
+        for element in self.resident.iter().take(count) {
+            element.encode(&mut bytes);
+        }
+
+        let offset = self
+            .pages
+            .last()
+            .map_or(0, |page| page.offset + page.bytes as u64);
+        let file = match &mut self.file {
+            Some(spill) => &mut spill.file,
+            None => &mut self.file.insert(SpillFile::create(&self.dir)?).file,
+        };
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(&bytes)?;
+
+        // Only forget the elements once they are safely written.
+        self.resident.drain(..count);
+        self.pages.push(Page {
+            offset,
+            bytes: bytes.len(),
+            count,
+        });
+        self.spilled += count;
+        Ok(())
+    }
+
+    fn reload_page(&mut self) -> Result<(), SpillError> {
+        let (page, spill) = match (self.pages.last(), &mut self.file) {
+            (Some(&page), Some(spill)) => (page, spill),
+            _ => return Ok(()),
+        };
+
+        let mut bytes = vec![0; page.bytes];
+        spill.file.seek(SeekFrom::Start(page.offset))?;
+        spill.file.read_exact(&mut bytes)?;
+
+        let mut input = bytes.as_slice();
+        let mut elements = Vec::with_capacity(page.count);
+        for _ in 0..page.count {
+            elements.push(T::decode(&mut input).ok_or(SpillError::Corrupt)?);
+        }
+
+        spill.file.set_len(page.offset)?;
+        self.pages.pop();
+        self.spilled -= page.count;
+        for element in elements.into_iter().rev() {
+            self.resident.push_front(element);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::{vec_deque, VecDeque};
+
+use crate::error::{BatchError, RejectedToken, StackError};
+use crate::iter::{Drain, Iter};
+use crate::op::{Op, Outcome};
+use crate::transaction::{Change, Journal, Savepoint};
+
+/// The most elements a new stack allocates room for before any are pushed.
+const PREALLOCATE_LIMIT: usize = 1024;
+
+/// What a push does when the stack already holds `capacity` elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Refuse the push with [`StackError::Overflow`].
+    #[default]
+    Reject,
+    /// Double the capacity and keep every element.
+    Grow,
+    /// Drop the bottom element to make room, keeping the most recent ones.
+    EvictBottom,
+    /// Replace the top element with the new one.
+    OverwriteTop,
+}
+
+impl OverflowPolicy {
+    /// Every policy, in the order of their numbers in binary files.
+    pub const ALL: [OverflowPolicy; 4] = [
+        OverflowPolicy::Reject,
+        OverflowPolicy::Grow,
+        OverflowPolicy::EvictBottom,
+        OverflowPolicy::OverwriteTop,
+    ];
+
+    /// Returns the policy's name as used on the command line and in files.
+    pub fn name(self) -> &'static str {
+        match self {
+            OverflowPolicy::Reject => "reject",
+            OverflowPolicy::Grow => "grow",
+            OverflowPolicy::EvictBottom => "evict-bottom",
+            OverflowPolicy::OverwriteTop => "overwrite-top",
+        }
+    }
+
+    /// Looks a policy up by the name returned from [`OverflowPolicy::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        OverflowPolicy::ALL
+            .iter()
+            .copied()
+            .find(|policy| policy.name() == name)
+    }
+}
+
+/// A last-in, first-out stack holding at most `capacity` elements.
+///
+/// The top of the stack is the back of `elements`, so its length is the only
+/// record of how many elements the stack holds. Eviction takes from the
+/// front, so it does not move the other elements.
+///
+/// While a transaction is open, every mutation is also recorded in `journal`
+/// so that it can be rolled back. `transactions` counts the transactions
+/// begun, telling the savepoints of one from those of another.
+#[derive(Debug, Clone)]
+pub struct Stack<T> {
+    elements: VecDeque<T>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    journal: Option<Journal<T>>,
+    transactions: u64,
+}
+
+impl<T> Stack<T> {
+    /// Creates an empty stack that can hold up to `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Stack::with_policy(capacity, OverflowPolicy::Reject)
+    }
+
+    /// Creates an empty stack that handles pushes beyond `capacity` according to `policy`.
+    pub fn with_policy(capacity: usize, policy: OverflowPolicy) -> Self {
+        Stack {
+            // The capacity is only a limit, so a huge one must not be
+            // allocated up front.
+            elements: VecDeque::with_capacity(capacity.min(PREALLOCATE_LIMIT)),
+            capacity,
+            policy,
+            journal: None,
+            transactions: 0,
+        }
+    }
+
+    /// Creates a full stack from `elements`, whose last element is the top.
+    pub fn from_vec(elements: Vec<T>) -> Self {
+        Stack {
+            capacity: elements.len(),
+            elements: VecDeque::from(elements),
+            policy: OverflowPolicy::Reject,
+            journal: None,
+            transactions: 0,
+        }
+    }
+
+    /// Returns the elements from bottom to top, consuming the stack.
+    pub fn into_vec(self) -> Vec<T> {
+        Vec::from(self.elements)
+    }
+
+    pub(crate) fn into_elements(self) -> VecDeque<T> {
+        self.elements
+    }
+
+    /// Pushes `element` on top of the stack.
+    ///
+    /// Fails with [`StackError::Overflow`] when the stack is full and the
+    /// policy is [`OverflowPolicy::Reject`].
+    pub fn push(&mut self, element: T) -> Result<(), StackError> {
+        self.push_displacing(element).map(|_| ())
+    }
+
+    /// Pushes `element` on top of the stack, returning the element the
+    /// overflow policy displaced to make room, if any.
+    ///
+    /// [`OverflowPolicy::EvictBottom`] and [`OverflowPolicy::OverwriteTop`]
+    /// still fail with [`StackError::Overflow`] on a zero-capacity stack.
+    pub fn push_displacing(&mut self, element: T) -> Result<Option<T>, StackError> {
+        let displaced = self.push_unchecked(element);
+        self.keep_contiguous();
+        self.debug_check_invariants();
+        displaced
+    }
+
+    fn push_unchecked(&mut self, element: T) -> Result<Option<T>, StackError> {
+        if self.elements.len() < self.capacity {
+            self.elements.push_back(element);
+            self.record(|_| Change::Pushed);
+            return Ok(None);
+        }
+
+        let overflow = StackError::Overflow {
+            capacity: self.capacity,
+        };
+
+        match self.policy {
+            OverflowPolicy::Reject => Err(overflow),
+            OverflowPolicy::Grow => {
+                let previous = self.capacity;
+                self.capacity = self.capacity.max(1).saturating_mul(2);
+                self.elements.push_back(element);
+                self.record(|_| Change::Grew { capacity: previous });
+                self.record(|_| Change::Pushed);
+                Ok(None)
+            }
+            OverflowPolicy::EvictBottom => {
+                let evicted = match self.elements.pop_front() {
+                    Some(evicted) => evicted,
+                    None => return Err(overflow),
+                };
+                self.elements.push_back(element);
+                self.record(|journal| Change::EvictedBottom(journal.copy(&evicted)));
+                self.record(|_| Change::Pushed);
+                Ok(Some(evicted))
+            }
+            OverflowPolicy::OverwriteTop => match self.elements.back_mut() {
+                Some(top) => {
+                    let overwritten = std::mem::replace(top, element);
+                    self.record(|journal| Change::Overwrote(journal.copy(&overwritten)));
+                    Ok(Some(overwritten))
+                }
+                None => Err(overflow),
+            },
+        }
+    }
+
+    /// Pushes every element or none of them.
+    ///
+    /// A batch that the overflow policy cannot absorb is refused as a whole,
+    /// reporting the 1-based positions of the elements that would not fit.
+    pub fn push_batch<I>(&mut self, elements: I) -> Result<(), BatchError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let elements: Vec<T> = elements.into_iter().collect();
+        let fits = self.room().unwrap_or(elements.len());
+
+        if elements.len() > fits {
+            let rejected = (fits + 1..=elements.len())
+                .map(|position| RejectedToken {
+                    position,
+                    error: StackError::Overflow {
+                        capacity: self.capacity,
+                    },
+                })
+                .collect();
+            return Err(BatchError { rejected });
+        }
+
+        for element in elements {
+            // Cannot fail: the whole batch was checked against the policy above.
+            let _ = self.push_displacing(element);
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Removes and returns the top element.
+    ///
+    /// Fails with [`StackError::Underflow`] when the stack is empty.
+    pub fn pop(&mut self) -> Result<T, StackError> {
+        let popped = self.elements.pop_back().ok_or(StackError::Underflow)?;
+        self.record(|journal| Change::Popped(journal.copy(&popped)));
+        self.debug_check_invariants();
+        Ok(popped)
+    }
+
+    /// Returns a reference to the top element without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.elements.back()
+    }
+
+    /// Removes every element, keeping the capacity.
+    pub fn clear(&mut self) {
+        match &mut self.journal {
+            Some(journal) => journal.record(Change::Cleared(std::mem::take(&mut self.elements))),
+            None => self.elements.clear(),
+        }
+        self.debug_check_invariants();
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.elements.len() >= self.capacity
+    }
+
+    /// Returns how many more elements the overflow policy accepts, or `None`
+    /// if it makes room for any number of them.
+    pub fn room(&self) -> Option<usize> {
+        match self.policy {
+            OverflowPolicy::Reject => Some(self.capacity - self.elements.len()),
+            OverflowPolicy::Grow => None,
+            OverflowPolicy::EvictBottom | OverflowPolicy::OverwriteTop if self.capacity == 0 => {
+                Some(0)
+            }
+            OverflowPolicy::EvictBottom | OverflowPolicy::OverwriteTop => None,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Returns the elements from bottom to top.
+    pub fn as_slice(&self) -> &[T] {
+        self.elements.as_slices().0
+    }
+
+    /// Iterates over the elements from top to bottom.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.elements)
+    }
+
+    /// Iterates over the elements from bottom to top.
+    pub fn iter_bottom_up(&self) -> vec_deque::Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    /// Pops every element, yielding them from top to bottom.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        if let Some(journal) = &mut self.journal {
+            let copies = self
+                .elements
+                .iter()
+                .map(|element| journal.copy(element))
+                .collect();
+            journal.record(Change::Cleared(copies));
+        }
+        // Checked before the drain borrows the elements; emptying the stack
+        // cannot break what held with them in place.
+        self.debug_check_invariants();
+        Drain::new(self.elements.drain(..))
+    }
+
+    /// Opens a transaction: from now on every mutation can be undone with
+    /// [`Stack::rollback`] or [`Stack::rollback_to`] until [`Stack::commit`].
+    ///
+    /// Fails with [`StackError::TransactionInProgress`] if one is already open.
+    pub fn begin(&mut self) -> Result<(), StackError>
+    where
+        T: Clone,
+    {
+        if self.journal.is_some() {
+            return Err(StackError::TransactionInProgress);
+        }
+
+        self.transactions += 1;
+        self.journal = Some(Journal::new(T::clone, self.transactions));
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Marks the current state of the open transaction.
+    pub fn savepoint(&mut self) -> Result<Savepoint, StackError> {
+        let savepoint = self
+            .journal
+            .as_mut()
+            .ok_or(StackError::NoTransaction)?
+            .savepoint();
+        self.debug_check_invariants();
+        Ok(savepoint)
+    }
+
+    /// Undoes everything done since `savepoint`, keeping the transaction open.
+    ///
+    /// Savepoints created after `savepoint` are discarded. Fails with
+    /// [`StackError::UnknownSavepoint`] for those, and for savepoints of
+    /// another transaction.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) -> Result<(), StackError> {
+        let journal = self.journal.as_mut().ok_or(StackError::NoTransaction)?;
+        journal.rollback_to(savepoint, &mut self.elements, &mut self.capacity)?;
+        self.keep_contiguous();
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Undoes everything done since [`Stack::begin`] and closes the transaction.
+    pub fn rollback(&mut self) -> Result<(), StackError> {
+        let journal = self.journal.take().ok_or(StackError::NoTransaction)?;
+        journal.rollback(&mut self.elements, &mut self.capacity);
+        self.keep_contiguous();
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Keeps everything done since [`Stack::begin`] and closes the transaction.
+    pub fn commit(&mut self) -> Result<(), StackError> {
+        self.journal.take().ok_or(StackError::NoTransaction)?;
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Returns the number of transactions begun on the stack, which is the
+    /// number of the open one, if any.
+    pub(crate) fn transactions(&self) -> u64 {
+        self.transactions
+    }
+
+    /// Applies `op` with the method it stands for.
+    ///
+    /// A rejected [`Op::PushBatch`] fails with the error of its first
+    /// rejected element.
+    pub fn apply(&mut self, op: Op<T>) -> Result<Outcome<T>, StackError>
+    where
+        T: Clone,
+    {
+        let outcome = match op {
+            Op::Push(element) => self
+                .push_displacing(element)?
+                .map_or(Outcome::Done, Outcome::Element),
+            Op::PushBatch(elements) => {
+                let capacity = self.capacity;
+                self.push_batch(elements).map_err(|err| {
+                    err.rejected
+                        .first()
+                        .map_or(StackError::Overflow { capacity }, |rejected| rejected.error)
+                })?;
+                Outcome::Done
+            }
+            Op::Pop => Outcome::Element(self.pop()?),
+            Op::Clear => {
+                self.clear();
+                Outcome::Done
+            }
+            Op::Begin => {
+                self.begin()?;
+                Outcome::Done
+            }
+            Op::Savepoint => Outcome::Savepoint(self.savepoint()?),
+            Op::RollbackTo(savepoint) => {
+                self.rollback_to(savepoint)?;
+                Outcome::Done
+            }
+            Op::Rollback => {
+                self.rollback()?;
+                Outcome::Done
+            }
+            Op::Commit => {
+                self.commit()?;
+                Outcome::Done
+            }
+            Op::Word(word) => {
+                self.word(word)?;
+                Outcome::Done
+            }
+        };
+        Ok(outcome)
+    }
+
+    /// Records the change built by `change` if a transaction is open.
+    fn record(&mut self, change: impl FnOnce(&Journal<T>) -> Change<T>) {
+        if let Some(journal) = &mut self.journal {
+            let change = change(journal);
+            journal.record(change);
+        }
+    }
+
+    /// Panics if the stack holds more elements than its capacity, or if the
+    /// open transaction's savepoints point past the end of its journal.
+    pub fn check_invariants(&self) {
+        let len = self.elements.len();
+
+        assert!(
+            len <= self.capacity,
+            "stack holds {} elements but its capacity is {}",
+            len,
+            self.capacity
+        );
+        assert!(
+            self.elements.as_slices().1.is_empty(),
+            "stack elements wrap around the end of their buffer"
+        );
+        if let Some(journal) = &self.journal {
+            journal.check_invariants();
+        }
+    }
+
+    /// Moves the elements back into one run once evicting from the bottom has
+    /// wrapped them around the end of the buffer, so [`Stack::as_slice`] can
+    /// borrow them as they are.
+    ///
+    /// The new buffer has room for as many elements again, so it takes at
+    /// least `len` more evictions to wrap next time and the copy stays
+    /// amortized O(1) per push.
+    fn keep_contiguous(&mut self) {
+        if self.elements.as_slices().1.is_empty() {
+            return;
+        }
+        let mut elements = VecDeque::with_capacity(self.elements.len() * 2);
+        elements.extend(self.elements.drain(..));
+        self.elements = elements;
+    }
+
+    /// Runs [`Stack::check_invariants`] after every mutation in debug builds.
+    fn debug_check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            self.check_invariants();
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+
+use crate::error::StackError;
+
+/// A single mutation of a stack, recorded so it can be undone.
+#[derive(Debug, Clone)]
+pub(crate) enum Change<T> {
+    Pushed,
+    Popped(T),
+    EvictedBottom(T),
+    Overwrote(T),
+    Grew { capacity: usize },
+    Cleared(VecDeque<T>),
+}
+
+/// A point inside a transaction that [`Stack::rollback_to`] can return to.
+///
+/// A savepoint belongs to the transaction it was made in, so a later
+/// transaction on the same stack refuses it even if it has made as many.
+///
+/// [`Stack::rollback_to`]: crate::Stack::rollback_to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Savepoint {
+    transaction: u64,
+    id: usize,
+}
+
+impl Savepoint {
+    /// Recreates the savepoint `id` of transaction `transaction`, as found
+    /// in a log.
+    pub(crate) fn from_parts(transaction: u64, id: usize) -> Self {
+        Savepoint { transaction, id }
+    }
+
+    /// Returns the number of the transaction the savepoint was made in,
+    /// counting the transactions begun on its stack from 1.
+    pub fn transaction(&self) -> u64 {
+        self.transaction
+    }
+
+    /// Returns the position of the savepoint in its transaction, from 1.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+/// The undo log of an open transaction.
+///
+/// Popped elements are handed to the caller, so the journal keeps its own
+/// copies made with `clone`, which `Stack::begin` captures from `T: Clone`.
+#[derive(Debug, Clone)]
+pub(crate) struct Journal<T> {
+    clone: fn(&T) -> T,
+    transaction: u64,
+    changes: Vec<Change<T>>,
+    savepoints: Vec<(Savepoint, usize)>,
+    next_id: usize,
+}
+
+impl<T> Journal<T> {
+    pub(crate) fn new(clone: fn(&T) -> T, transaction: u64) -> Self {
+        Journal {
+            clone,
+            transaction,
+            changes: Vec::new(),
+            savepoints: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    pub(crate) fn copy(&self, element: &T) -> T {
+        (self.clone)(element)
+    }
+
+    pub(crate) fn record(&mut self, change: Change<T>) {
+        self.changes.push(change);
+    }
+
+    pub(crate) fn savepoint(&mut self) -> Savepoint {
+        let savepoint = Savepoint {
+            transaction: self.transaction,
+            id: self.next_id,
+        };
+        self.next_id += 1;
+        self.savepoints.push((savepoint, self.changes.len()));
+        savepoint
+    }
+
+    /// Panics if a savepoint marks a point past the end of the journal, or
+    /// the savepoints are out of order.
+    pub(crate) fn check_invariants(&self) {
+        let mut previous = (0, 0);
+        for &(savepoint, mark) in &self.savepoints {
+            assert!(
+                mark <= self.changes.len(),
+                "savepoint {} marks change {} but the journal holds {}",
+                savepoint.id,
+                mark,
+                self.changes.len()
+            );
+            assert!(
+                savepoint.id > previous.0 && mark >= previous.1,
+                "savepoint {} at change {} is out of order",
+                savepoint.id,
+                mark
+            );
+            previous = (savepoint.id, mark);
+        }
+        assert!(
+            self.next_id > previous.0,
+            "next savepoint id {} is already in use",
+            self.next_id
+        );
+    }
+
+    /// Undoes every change made since `savepoint`, which stays usable while
+    /// the savepoints created after it are forgotten.
+    pub(crate) fn rollback_to(
+        &mut self,
+        savepoint: Savepoint,
+        elements: &mut VecDeque<T>,
+        capacity: &mut usize,
+    ) -> Result<(), StackError> {
+        let index = self
+            .savepoints
+            .iter()
+            .position(|&(candidate, _)| candidate == savepoint)
+            .ok_or(StackError::UnknownSavepoint)?;
+        let mark = self.savepoints[index].1;
+
+        self.savepoints.truncate(index + 1);
+        self.undo(mark, elements, capacity);
+        Ok(())
+    }
+
+    /// Undoes every change in the journal.
+    pub(crate) fn rollback(mut self, elements: &mut VecDeque<T>, capacity: &mut usize) {
+        self.undo(0, elements, capacity);
+    }
+
+    fn undo(&mut self, mark: usize, elements: &mut VecDeque<T>, capacity: &mut usize) {
+        while self.changes.len() > mark {
+            match self.changes.pop() {
+                Some(Change::Pushed) => {
+                    elements.pop_back();
+                }
+                Some(Change::Popped(element)) => elements.push_back(element),
+                Some(Change::EvictedBottom(element)) => elements.push_front(element),
+                Some(Change::Overwrote(element)) => {
+                    if let Some(top) = elements.back_mut() {
+                        *top = element;
+                    }
+                }
+                Some(Change::Grew { capacity: previous }) => *capacity = previous,
+                Some(Change::Cleared(previous)) => *elements = previous,
+                None => break,
+            }
+        }
+    }
+}
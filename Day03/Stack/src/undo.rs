@@ -0,0 +1,136 @@
+use crate::error::StackError;
+use crate::stack::{OverflowPolicy, Stack};
+
+/// An edit that knows how to apply itself to a target and how to take
+/// itself back.
+pub trait Command {
+    type Target;
+
+    fn apply(&mut self, target: &mut Self::Target);
+
+    /// Undoes the effect of the last `apply`.
+    fn revert(&mut self, target: &mut Self::Target);
+}
+
+/// Undo/redo history built on two stacks of steps, where each step is one
+/// command or a group of commands undone together.
+///
+/// The undo stack holds at most `limit` steps and forgets the oldest when a
+/// new one arrives. Executing a new command discards the redo stack, since
+/// the steps on it no longer follow from the current state.
+pub struct UndoManager<C> {
+    undo: Stack<Vec<C>>,
+    redo: Stack<Vec<C>>,
+    group: Option<Vec<C>>,
+    group_depth: usize,
+}
+
+impl<C: Command> UndoManager<C> {
+    /// Creates a history that remembers up to `limit` steps.
+    pub fn with_limit(limit: usize) -> Self {
+        UndoManager {
+            undo: Stack::with_policy(limit, OverflowPolicy::EvictBottom),
+            redo: Stack::with_capacity(limit),
+            group: None,
+            group_depth: 0,
+        }
+    }
+
+    /// Applies `command` to `target` and records it as the newest step, or
+    /// as part of the open group.
+    pub fn execute(&mut self, mut command: C, target: &mut C::Target) {
+        command.apply(target);
+        self.redo.clear();
+
+        match &mut self.group {
+            Some(group) => group.push(command),
+            None => {
+                // Only fails with a zero limit, which keeps no history.
+                let _ = self.undo.push(vec![command]);
+            }
+        }
+    }
+
+    /// Starts collecting commands into one step. Groups may be nested; the
+    /// step is recorded when the outermost group ends.
+    pub fn begin_group(&mut self) {
+        self.group_depth += 1;
+        self.group.get_or_insert_with(Vec::new);
+    }
+
+    /// Ends the innermost group.
+    pub fn end_group(&mut self) {
+        if self.group_depth == 0 {
+            return;
+        }
+
+        self.group_depth -= 1;
+        if self.group_depth == 0 {
+            self.close_group();
+        }
+    }
+
+    /// Reverts the newest step, closing any open group first.
+    ///
+    /// Fails with [`StackError::Underflow`] when there is nothing to undo.
+    pub fn undo(&mut self, target: &mut C::Target) -> Result<(), StackError> {
+        self.group_depth = 0;
+        self.close_group();
+
+        let mut step = self.undo.pop()?;
+        for command in step.iter_mut().rev() {
+            command.revert(target);
+        }
+        self.redo.push(step)
+    }
+
+    /// Applies the most recently undone step again.
+    ///
+    /// Fails with [`StackError::Underflow`] when there is nothing to redo.
+    pub fn redo(&mut self, target: &mut C::Target) -> Result<(), StackError> {
+        let mut step = self.redo.pop()?;
+        for command in step.iter_mut() {
+            command.apply(target);
+        }
+        self.undo.push(step)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.group.as_ref().is_some_and(|group| !group.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns the number of steps that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns the number of steps that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.undo.capacity()
+    }
+
+    /// Forgets the whole history without touching the target.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+        self.group_depth = 0;
+    }
+
+    fn close_group(&mut self) {
+        if let Some(group) = self.group.take() {
+            if !group.is_empty() {
+                // Only fails with a zero limit, which keeps no history.
+                let _ = self.undo.push(group);
+            }
+        }
+    }
+}
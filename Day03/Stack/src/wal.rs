@@ -0,0 +1,716 @@
+//! A write-ahead log that lets a [`Stack`] survive crashes.
+//!
+//! A durable stack lives in a directory holding two files: [`SNAPSHOT_FILE`],
+//! a binary [`snapshot`] of the stack, and [`LOG_FILE`], every [`Op`] applied
+//! since that snapshot was taken. Each op is appended to the log before it is
+//! applied, so reopening the directory replays the log over the snapshot and
+//! gets back the stack as it was. Ops that failed are logged too; replaying
+//! them fails the same way and changes nothing.
+//!
+//! The log starts with a header and is followed by one record per op, with
+//! every number little-endian:
+//!
+//! | Field    | Size     | Contents                                         |
+//! |----------|----------|--------------------------------------------------|
+//! | magic    | 4 bytes  | `STKW`                                           |
+//! | version  | `u16`    | `1`                                              |
+//! | type     | `u8`     | [`Codec::TYPE_TAG`] of the element type          |
+//! | base     | `u32`    | checksum of the snapshot the log applies to      |
+//!
+//! | Field    | Size     | Contents                                         |
+//! |----------|----------|--------------------------------------------------|
+//! | length   | `u32`    | length of the payload                            |
+//! | checksum | `u32`    | CRC-32 of the payload                            |
+//! | payload  | variable | op code, then the op's operands                  |
+//!
+//! A record cut short by a crash fails its checksum, and it is dropped along
+//! with anything after it. A transaction still open at the end of the log is
+//! rolled back, since it was never committed.
+//!
+//! Once the log holds [`WalOptions::compact_after`] records it is compacted:
+//! the stack is written to a new snapshot and the log is emptied. A log whose
+//! base does not match the snapshot predates it and is discarded, so a crash
+//! between those two steps never replays an op twice. For the same reason,
+//! if emptying the log fails after the snapshot was written, nothing more is
+//! appended until it has been emptied.
+//!
+//! [`snapshot`]: crate::snapshot
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::codec::{self, Codec};
+use crate::error::StackError;
+use crate::forth::Word;
+use crate::op::{Op, Outcome};
+use crate::snapshot::{self, SnapshotError};
+use crate::stack::{OverflowPolicy, Stack};
+use crate::transaction::Savepoint;
+
+/// The first bytes of a log.
+pub const MAGIC: [u8; 4] = *b"STKW";
+/// The newest log version this crate reads and the one it writes.
+pub const VERSION: u16 = 1;
+/// The name of the snapshot inside a durable stack's directory.
+pub const SNAPSHOT_FILE: &str = "snapshot.bin";
+/// The name of the log inside a durable stack's directory.
+pub const LOG_FILE: &str = "wal.log";
+
+const HEADER_LEN: usize = 11;
+const RECORD_HEADER_LEN: usize = 8;
+
+const PUSH: u8 = 1;
+const PUSH_BATCH: u8 = 2;
+const POP: u8 = 3;
+const CLEAR: u8 = 4;
+const BEGIN: u8 = 5;
+const SAVEPOINT: u8 = 6;
+const ROLLBACK_TO: u8 = 7;
+const ROLLBACK: u8 = 8;
+const COMMIT: u8 = 9;
+const WORD: u8 = 10;
+
+/// The words in the order their index is logged in.
+const WORDS: [Word; 12] = [
+    Word::Dup,
+    Word::Drop,
+    Word::Swap,
+    Word::Over,
+    Word::Rot,
+    Word::MinusRot,
+    Word::Nip,
+    Word::Tuck,
+    Word::Pick(0),
+    Word::Roll(0),
+    Word::TwoDup,
+    Word::TwoSwap,
+];
+
+/// Returns the index of `word` in [`WORDS`].
+fn word_index(word: Word) -> u8 {
+    match word {
+        Word::Dup => 0,
+        Word::Drop => 1,
+        Word::Swap => 2,
+        Word::Over => 3,
+        Word::Rot => 4,
+        Word::MinusRot => 5,
+        Word::Nip => 6,
+        Word::Tuck => 7,
+        Word::Pick(_) => 8,
+        Word::Roll(_) => 9,
+        Word::TwoDup => 10,
+        Word::TwoSwap => 11,
+    }
+}
+
+/// When appended records are flushed to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    /// After every record. Nothing acknowledged is ever lost.
+    #[default]
+    Always,
+    /// After every `n` records, and when the log is dropped. A crash loses
+    /// at most the last `n - 1` records.
+    Every(usize),
+    /// Whenever the operating system gets to it.
+    Never,
+}
+
+/// How a [`Wal`] behaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalOptions {
+    pub sync: SyncPolicy,
+    /// The number of records after which the log is compacted, or `None`
+    /// to only compact when asked.
+    pub compact_after: Option<usize>,
+}
+
+impl Default for WalOptions {
+    fn default() -> Self {
+        WalOptions {
+            sync: SyncPolicy::Always,
+            compact_after: Some(1024),
+        }
+    }
+}
+
+/// Errors returned by [`Wal`] and [`DurableStack`] operations.
+#[derive(Debug)]
+pub enum WalError {
+    Io(io::Error),
+    Snapshot(SnapshotError),
+    Stack(StackError),
+    /// The log cannot be used with this snapshot or element type.
+    Corrupt(String),
+}
+
+impl fmt::Display for WalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalError::Io(err) => write!(f, "log error: {}", err),
+            WalError::Snapshot(err) => err.fmt(f),
+            WalError::Stack(err) => err.fmt(f),
+            WalError::Corrupt(reason) => write!(f, "log is corrupted: {}", reason),
+        }
+    }
+}
+
+impl Error for WalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WalError::Io(err) => Some(err),
+            WalError::Snapshot(err) => Some(err),
+            WalError::Stack(err) => Some(err),
+            WalError::Corrupt(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for WalError {
+    fn from(err: io::Error) -> Self {
+        WalError::Io(err)
+    }
+}
+
+impl From<SnapshotError> for WalError {
+    fn from(err: SnapshotError) -> Self {
+        WalError::Snapshot(err)
+    }
+}
+
+impl From<StackError> for WalError {
+    fn from(err: StackError) -> Self {
+        WalError::Stack(err)
+    }
+}
+
+fn corrupt(reason: impl Into<String>) -> WalError {
+    WalError::Corrupt(reason.into())
+}
+
+/// The log of a durable stack's directory.
+///
+/// A `Wal` only records ops; the caller applies them to the stack returned by
+/// [`Wal::open`]. [`DurableStack`] does both.
+#[derive(Debug)]
+pub struct Wal<T> {
+    dir: PathBuf,
+    file: File,
+    options: WalOptions,
+    records: usize,
+    unsynced: usize,
+    /// The transactions begun on the stack in the snapshot.
+    transactions: u64,
+    /// The checksum of a snapshot written by a compaction that then failed
+    /// to reset the log. The log predates that snapshot, so it must be reset
+    /// before anything more is appended to it.
+    pending_reset: Option<u32>,
+    marker: PhantomData<fn(T)>,
+}
+
+impl<T: Codec + Clone> Wal<T> {
+    /// Opens the durable stack in `dir`, creating it if needed, and rebuilds
+    /// the stack from its snapshot and log.
+    ///
+    /// `capacity` and `policy` are only used when the directory holds no
+    /// stack yet.
+    pub fn open(
+        dir: impl AsRef<Path>,
+        capacity: usize,
+        policy: OverflowPolicy,
+        options: WalOptions,
+    ) -> Result<(Stack<T>, Self), WalError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let snapshot_path = dir.join(SNAPSHOT_FILE);
+        let log_path = dir.join(LOG_FILE);
+
+        let (mut stack, base) = if snapshot_path.exists() {
+            let bytes = fs::read(&snapshot_path)?;
+            let stack = snapshot::read_binary(&bytes[..])?;
+            (stack, snapshot_checksum(&bytes))
+        } else if log_path.exists() {
+            return Err(corrupt(format!("{} is missing", SNAPSHOT_FILE)));
+        } else {
+            let stack = Stack::with_policy(capacity, policy);
+            let base = write_snapshot(dir, &stack)?;
+            (stack, base)
+        };
+
+        let records = if log_path.exists() {
+            let bytes = fs::read(&log_path)?;
+            check_header::<T>(&bytes)?;
+            if bytes[HEADER_LEN - 4..HEADER_LEN] == base.to_le_bytes() {
+                let (records, end) = replay(&mut stack, &bytes[HEADER_LEN..]);
+                if end < bytes.len() - HEADER_LEN {
+                    let file = OpenOptions::new().write(true).open(&log_path)?;
+                    file.set_len((HEADER_LEN + end) as u64)?;
+                    file.sync_all()?;
+                }
+                Some(records)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        let records = match records {
+            Some(records) => records,
+            None => {
+                reset_log::<T>(&log_path, base)?;
+                0
+            }
+        };
+
+        let mut wal = Wal {
+            dir: dir.to_path_buf(),
+            file: OpenOptions::new().append(true).open(&log_path)?,
+            options,
+            records,
+            unsynced: 0,
+            transactions: 0,
+            pending_reset: None,
+            marker: PhantomData,
+        };
+
+        if stack.in_transaction() {
+            wal.append(&Op::Rollback)?;
+            stack.rollback()?;
+        }
+
+        Ok((stack, wal))
+    }
+
+    /// Appends `op` to the log, syncing it as the [`SyncPolicy`] says.
+    pub fn append(&mut self, op: &Op<T>) -> Result<(), WalError> {
+        if let Some(base) = self.pending_reset {
+            self.reset(base)?;
+        }
+
+        let mut payload = Vec::new();
+        encode_op(op, self.transactions, &mut payload);
+
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+        (payload.len() as u32).encode(&mut record);
+        snapshot::crc32(&payload).encode(&mut record);
+        record.extend_from_slice(&payload);
+
+        self.file.write_all(&record)?;
+        self.records += 1;
+        self.unsynced += 1;
+
+        match self.options.sync {
+            SyncPolicy::Always => self.sync()?,
+            SyncPolicy::Every(n) if self.unsynced >= n => self.sync()?,
+            SyncPolicy::Every(_) | SyncPolicy::Never => {}
+        }
+        Ok(())
+    }
+
+    /// Flushes every appended record to disk.
+    pub fn sync(&mut self) -> Result<(), WalError> {
+        self.file.sync_data()?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    /// Replaces the snapshot with `stack` and empties the log.
+    ///
+    /// Fails with [`StackError::TransactionInProgress`] while `stack` has a
+    /// transaction open, since a snapshot cannot hold one.
+    pub fn compact(&mut self, stack: &Stack<T>) -> Result<(), WalError> {
+        if stack.in_transaction() {
+            return Err(StackError::TransactionInProgress.into());
+        }
+
+        let base = write_snapshot(&self.dir, stack)?;
+        self.transactions = stack.transactions();
+        self.pending_reset = Some(base);
+        self.reset(base)
+    }
+
+    /// Empties the log, basing it on the snapshot with checksum `base`.
+    fn reset(&mut self, base: u32) -> Result<(), WalError> {
+        let log_path = self.dir.join(LOG_FILE);
+        reset_log::<T>(&log_path, base)?;
+
+        self.file = OpenOptions::new().append(true).open(&log_path)?;
+        self.records = 0;
+        self.unsynced = 0;
+        self.pending_reset = None;
+        Ok(())
+    }
+
+    /// Returns whether the log has reached [`WalOptions::compact_after`].
+    pub fn is_compaction_due(&self) -> bool {
+        self.options
+            .compact_after
+            .is_some_and(|limit| self.records >= limit)
+    }
+
+    /// Returns the number of records in the log.
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl<T> Drop for Wal<T> {
+    fn drop(&mut self) {
+        if self.unsynced > 0 && self.options.sync != SyncPolicy::Never {
+            let _ = self.file.sync_data();
+        }
+    }
+}
+
+/// A [`Stack`] whose every change is logged to a [`Wal`] before it is made.
+#[derive(Debug)]
+pub struct DurableStack<T> {
+    stack: Stack<T>,
+    wal: Wal<T>,
+    /// Why the last compaction [`DurableStack::apply`] started failed, until
+    /// it is taken.
+    compaction_error: Option<WalError>,
+}
+
+impl<T: Codec + Clone> DurableStack<T> {
+    /// Opens the durable stack in `dir`, as [`Wal::open`] does.
+    pub fn open(
+        dir: impl AsRef<Path>,
+        capacity: usize,
+        policy: OverflowPolicy,
+        options: WalOptions,
+    ) -> Result<Self, WalError> {
+        let (stack, wal) = Wal::open(dir, capacity, policy, options)?;
+        Ok(DurableStack {
+            stack,
+            wal,
+            compaction_error: None,
+        })
+    }
+
+    /// Logs `op`, applies it, and compacts the log if it is due and no
+    /// transaction is open.
+    ///
+    /// The result is the op's own: if compacting fails, the log is left as
+    /// it was, the error is kept for [`DurableStack::take_compaction_error`]
+    /// and compacting is tried again after the next op.
+    pub fn apply(&mut self, op: Op<T>) -> Result<Outcome<T>, WalError> {
+        self.apply_with(op, Stack::apply)
+    }
+
+    /// Like [`DurableStack::apply`], but applies `op` with `apply`, which
+    /// must do what [`Stack::apply`] does and may also record the op, for
+    /// instance in a [`History`].
+    ///
+    /// [`History`]: crate::History
+    pub fn apply_with(
+        &mut self,
+        op: Op<T>,
+        apply: impl FnOnce(&mut Stack<T>, Op<T>) -> Result<Outcome<T>, StackError>,
+    ) -> Result<Outcome<T>, WalError> {
+        self.wal.append(&op)?;
+        let outcome = apply(&mut self.stack, op);
+        if self.wal.is_compaction_due() && !self.stack.in_transaction() {
+            if let Err(err) = self.wal.compact(&self.stack) {
+                self.compaction_error = Some(err);
+            }
+        }
+        Ok(outcome?)
+    }
+
+    /// Returns why the last compaction started by an op failed, if it has
+    /// not been taken yet.
+    pub fn take_compaction_error(&mut self) -> Option<WalError> {
+        self.compaction_error.take()
+    }
+
+    /// Replaces the stack wholesale, making the new one the snapshot.
+    pub fn replace(&mut self, stack: Stack<T>) -> Result<(), WalError> {
+        self.wal.compact(&stack)?;
+        self.stack = stack;
+        Ok(())
+    }
+
+    /// Pushes `element`, returning the element it displaced, if any.
+    pub fn push(&mut self, element: T) -> Result<Option<T>, WalError> {
+        match self.apply(Op::Push(element))? {
+            Outcome::Element(displaced) => Ok(Some(displaced)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn pop(&mut self) -> Result<T, WalError> {
+        match self.apply(Op::Pop)? {
+            Outcome::Element(element) => Ok(element),
+            _ => unreachable!("a successful pop returns an element"),
+        }
+    }
+
+    /// Writes the stack to a new snapshot and empties the log.
+    pub fn compact(&mut self) -> Result<(), WalError> {
+        self.wal.compact(&self.stack)
+    }
+
+    pub fn sync(&mut self) -> Result<(), WalError> {
+        self.wal.sync()
+    }
+}
+
+impl<T> DurableStack<T> {
+    pub fn stack(&self) -> &Stack<T> {
+        &self.stack
+    }
+
+    pub fn wal(&self) -> &Wal<T> {
+        &self.wal
+    }
+}
+
+/// Writes `stack` as the snapshot in `dir`, returning its checksum.
+fn write_snapshot<T: Codec>(dir: &Path, stack: &Stack<T>) -> Result<u32, WalError> {
+    let mut bytes = Vec::new();
+    snapshot::write_binary(stack, &mut bytes)?;
+    snapshot::write_atomically(&dir.join(SNAPSHOT_FILE), |file| {
+        file.write_all(&bytes)?;
+        Ok(())
+    })?;
+    sync_dir(dir);
+    Ok(snapshot_checksum(&bytes))
+}
+
+/// Returns the checksum stored at the end of a binary snapshot.
+fn snapshot_checksum(bytes: &[u8]) -> u32 {
+    let mut input = &bytes[bytes.len().saturating_sub(4)..];
+    u32::decode(&mut input).unwrap_or(0)
+}
+
+/// Replaces the log at `path` with an empty one based on the snapshot with
+/// checksum `base`.
+fn reset_log<T: Codec>(path: &Path, base: u32) -> Result<(), WalError> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(&MAGIC);
+    VERSION.encode(&mut header);
+    T::TYPE_TAG.encode(&mut header);
+    base.encode(&mut header);
+
+    snapshot::write_atomically(path, |file| {
+        file.write_all(&header)?;
+        Ok(())
+    })?;
+    if let Some(dir) = path.parent() {
+        sync_dir(dir);
+    }
+    Ok(())
+}
+
+/// Makes renames inside `dir` durable where the platform allows it.
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+fn check_header<T: Codec>(bytes: &[u8]) -> Result<(), WalError> {
+    if bytes.len() < HEADER_LEN || !bytes.starts_with(&MAGIC) {
+        return Err(corrupt(format!("{} is not a stack log", LOG_FILE)));
+    }
+
+    let mut input = &bytes[MAGIC.len()..];
+    let version = u16::decode(&mut input).unwrap_or(0);
+    if version > VERSION {
+        return Err(corrupt(format!(
+            "log version {} is newer than the supported version {}",
+            version, VERSION
+        )));
+    }
+
+    let tag = u8::decode(&mut input).unwrap_or(0);
+    if tag != T::TYPE_TAG {
+        return Err(corrupt(format!(
+            "log holds {} elements, expected {}",
+            codec::type_name(tag).unwrap_or("unknown"),
+            T::TYPE_NAME
+        )));
+    }
+    Ok(())
+}
+
+/// Applies every intact record in `records` to `stack`, returning how many
+/// there were and where the last one ends.
+fn replay<T: Codec + Clone>(stack: &mut Stack<T>, records: &[u8]) -> (usize, usize) {
+    let mut count = 0;
+    let mut end = 0;
+
+    while let Some((op, len)) = read_record(&records[end..]) {
+        // Ops that failed when they were logged fail again and change nothing.
+        let _ = stack.apply(op);
+        count += 1;
+        end += len;
+    }
+    (count, end)
+}
+
+/// Decodes the record at the start of `bytes`, returning its op and length.
+fn read_record<T: Codec>(bytes: &[u8]) -> Option<(Op<T>, usize)> {
+    let mut input = bytes;
+    let len = u32::decode(&mut input)? as usize;
+    let checksum = u32::decode(&mut input)?;
+    let payload = input.get(..len)?;
+    if snapshot::crc32(payload) != checksum {
+        return None;
+    }
+    Some((decode_op(payload)?, RECORD_HEADER_LEN + len))
+}
+
+/// Encodes `op` for a log whose snapshot was taken after `transactions`
+/// transactions had begun.
+fn encode_op<T: Codec>(op: &Op<T>, transactions: u64, out: &mut Vec<u8>) {
+    match op {
+        Op::Push(element) => {
+            PUSH.encode(out);
+            element.encode(out);
+        }
+        Op::PushBatch(elements) => {
+            PUSH_BATCH.encode(out);
+            (elements.len() as u64).encode(out);
+            for element in elements {
+                element.encode(out);
+            }
+        }
+        Op::Pop => POP.encode(out),
+        Op::Clear => CLEAR.encode(out),
+        Op::Begin => BEGIN.encode(out),
+        Op::Savepoint => SAVEPOINT.encode(out),
+        Op::RollbackTo(savepoint) => {
+            ROLLBACK_TO.encode(out);
+            // The snapshot does not keep the stack's transaction count, so
+            // the transaction is counted from the snapshot, as replay counts.
+            // One from before the snapshot is refused whatever it becomes.
+            let transaction = savepoint
+                .transaction()
+                .checked_sub(transactions)
+                .unwrap_or(u64::MAX);
+            transaction.encode(out);
+            (savepoint.id() as u64).encode(out);
+        }
+        Op::Rollback => ROLLBACK.encode(out),
+        Op::Commit => COMMIT.encode(out),
+        Op::Word(word) => {
+            WORD.encode(out);
+            word_index(*word).encode(out);
+            if let Word::Pick(n) | Word::Roll(n) = word {
+                (*n as u64).encode(out);
+            }
+        }
+    }
+}
+
+fn decode_op<T: Codec>(mut input: &[u8]) -> Option<Op<T>> {
+    let op = match u8::decode(&mut input)? {
+        PUSH => Op::Push(T::decode(&mut input)?),
+        PUSH_BATCH => {
+            let count = u64::decode(&mut input)?;
+            let mut elements = Vec::new();
+            for _ in 0..count {
+                elements.push(T::decode(&mut input)?);
+            }
+            Op::PushBatch(elements)
+        }
+        POP => Op::Pop,
+        CLEAR => Op::Clear,
+        BEGIN => Op::Begin,
+        SAVEPOINT => Op::Savepoint,
+        ROLLBACK_TO => {
+            let transaction = u64::decode(&mut input)?;
+            let id = u64::decode(&mut input)? as usize;
+            Op::RollbackTo(Savepoint::from_parts(transaction, id))
+        }
+        ROLLBACK => Op::Rollback,
+        COMMIT => Op::Commit,
+        WORD => {
+            let word = *WORDS.get(usize::from(u8::decode(&mut input)?))?;
+            Op::Word(match word {
+                Word::Pick(_) => Word::Pick(u64::decode(&mut input)? as usize),
+                Word::Roll(_) => Word::Roll(u64::decode(&mut input)? as usize),
+                _ => word,
+            })
+        }
+        _ => return None,
+    };
+
+    if input.is_empty() {
+        Some(op)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_op_round_trips() {
+        let mut ops = vec![
+            Op::Push(String::from("a\nb")),
+            Op::PushBatch(vec![String::new(), String::from("c")]),
+            Op::PushBatch(Vec::new()),
+            Op::Pop,
+            Op::Clear,
+            Op::Begin,
+            Op::Savepoint,
+            Op::RollbackTo(Savepoint::from_parts(3, 2)),
+            Op::Rollback,
+            Op::Commit,
+        ];
+        ops.extend(WORDS.iter().map(|&word| Op::Word(word)));
+        ops.push(Op::Word(Word::Pick(7)));
+        ops.push(Op::Word(Word::Roll(usize::MAX >> 1)));
+
+        for op in ops {
+            let mut payload = Vec::new();
+            encode_op(&op, 0, &mut payload);
+            assert_eq!(decode_op::<String>(&payload), Some(op.clone()), "{:?}", op);
+            assert_eq!(decode_op::<String>(&payload[..payload.len() - 1]), None);
+        }
+    }
+
+    #[test]
+    fn word_indexes_match_the_table() {
+        for (index, &word) in WORDS.iter().enumerate() {
+            assert_eq!(usize::from(word_index(word)), index, "{}", word);
+        }
+    }
+
+    #[test]
+    fn savepoints_are_counted_from_the_snapshot() {
+        let op = Op::<i32>::RollbackTo(Savepoint::from_parts(5, 1));
+
+        let mut payload = Vec::new();
+        encode_op(&op, 3, &mut payload);
+        let decoded = decode_op::<i32>(&payload);
+        assert_eq!(decoded, Some(Op::RollbackTo(Savepoint::from_parts(2, 1))));
+
+        payload.clear();
+        encode_op(&op, 6, &mut payload);
+        let decoded = decode_op::<i32>(&payload);
+        assert_eq!(
+            decoded,
+            Some(Op::RollbackTo(Savepoint::from_parts(u64::MAX, 1)))
+        );
+    }
+}
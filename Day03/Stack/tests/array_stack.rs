@@ -0,0 +1,88 @@
+//! Uses nothing but `ArrayStack` and the errors, so it also runs with
+//! `--no-default-features`, against the `no_std` build of the crate.
+
+use std::cell::Cell;
+
+use rust::{ArrayStack, StackError};
+
+mod common;
+
+use common::Counted;
+
+#[test]
+fn push_and_pop_are_last_in_first_out() {
+    let mut stack: ArrayStack<i32, 3> = ArrayStack::new();
+
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.peek(), Some(&2));
+    assert_eq!(stack.as_slice(), &[1, 2]);
+    assert_eq!(stack.pop(), Ok(2));
+    assert_eq!(stack.pop(), Ok(1));
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn push_overflows_at_capacity() {
+    let mut stack: ArrayStack<i32, 2> = ArrayStack::new();
+
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert!(stack.is_full());
+    assert_eq!(stack.push(3), Err(StackError::Overflow { capacity: 2 }));
+    assert_eq!(stack.as_slice(), &[1, 2]);
+}
+
+#[test]
+fn zero_capacity_stack_overflows_at_once() {
+    let mut stack: ArrayStack<i32, 0> = ArrayStack::new();
+
+    assert_eq!(stack.push(1), Err(StackError::Overflow { capacity: 0 }));
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+}
+
+#[test]
+fn clear_empties_the_stack_and_drops_each_element_once() {
+    let drops = Cell::new(0);
+    let mut stack: ArrayStack<Counted, 4> = ArrayStack::new();
+    for _ in 0..3 {
+        stack.push(Counted(&drops)).unwrap();
+    }
+
+    stack.clear();
+    assert!(stack.is_empty());
+    assert_eq!(drops.get(), 3);
+
+    stack.push(Counted(&drops)).unwrap();
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+fn clone_is_independent() {
+    let mut stack: ArrayStack<String, 3> = ArrayStack::new();
+    stack.push("a".to_string()).unwrap();
+    stack.push("b".to_string()).unwrap();
+
+    let mut clone = stack.clone();
+    clone.pop().unwrap();
+    clone.push("c".to_string()).unwrap();
+
+    assert_eq!(stack.as_slice(), ["a", "b"]);
+    assert_eq!(clone.as_slice(), ["a", "c"]);
+    assert_eq!(clone.iter().collect::<Vec<_>>(), ["c", "a"]);
+}
+
+#[test]
+fn dropping_the_stack_drops_only_the_elements_left() {
+    let drops = Cell::new(0);
+    {
+        let mut stack: ArrayStack<Counted, 4> = ArrayStack::new();
+        for _ in 0..3 {
+            stack.push(Counted(&drops)).unwrap();
+        }
+        drop(stack.pop().unwrap());
+        assert_eq!(drops.get(), 1);
+    }
+    assert_eq!(drops.get(), 3);
+}
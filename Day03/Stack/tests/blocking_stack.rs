@@ -0,0 +1,142 @@
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rust::{BlockingStack, StackError};
+
+const ITEMS: usize = 10_000;
+
+#[test]
+fn blocking_push_and_pop_pass_every_item() {
+    let stack = Arc::new(BlockingStack::new(4));
+
+    let producer = {
+        let stack = Arc::clone(&stack);
+        thread::spawn(move || {
+            for item in 0..ITEMS {
+                stack.push(item).unwrap();
+            }
+        })
+    };
+
+    let mut received: Vec<usize> = (0..ITEMS).map(|_| stack.pop().unwrap()).collect();
+    producer.join().unwrap();
+
+    received.sort_unstable();
+    assert!(received.into_iter().eq(0..ITEMS));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn push_waits_for_room() {
+    let stack = Arc::new(BlockingStack::new(1));
+    stack.push(1).unwrap();
+
+    let producer = {
+        let stack = Arc::clone(&stack);
+        thread::spawn(move || stack.push(2))
+    };
+
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.pop(), Ok(1));
+    producer.join().unwrap().unwrap();
+    assert_eq!(stack.pop(), Ok(2));
+}
+
+#[test]
+fn pop_timeout_times_out_on_an_empty_stack() {
+    let stack: BlockingStack<i32> = BlockingStack::new(1);
+    let timeout = Duration::from_millis(50);
+
+    let started = Instant::now();
+    assert_eq!(stack.pop_timeout(timeout), Err(StackError::TimedOut));
+    assert!(started.elapsed() >= timeout);
+}
+
+#[test]
+fn pop_timeout_takes_an_item_pushed_while_waiting() {
+    let stack = Arc::new(BlockingStack::new(1));
+
+    let producer = {
+        let stack = Arc::clone(&stack);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            stack.push(7).unwrap();
+        })
+    };
+
+    assert_eq!(stack.pop_timeout(Duration::from_secs(10)), Ok(7));
+    producer.join().unwrap();
+}
+
+#[test]
+fn pop_timeout_without_a_deadline_waits_like_pop() {
+    let stack: Arc<BlockingStack<i32>> = Arc::new(BlockingStack::new(1));
+
+    let producer = {
+        let stack = Arc::clone(&stack);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            stack.push(7).unwrap();
+            stack.close();
+        })
+    };
+
+    assert_eq!(stack.pop_timeout(Duration::MAX), Ok(7));
+    assert_eq!(stack.pop_timeout(Duration::MAX), Err(StackError::Closed));
+    producer.join().unwrap();
+}
+
+#[test]
+fn close_wakes_blocked_consumers() {
+    let stack: Arc<BlockingStack<i32>> = Arc::new(BlockingStack::new(1));
+    let barrier = Arc::new(Barrier::new(3));
+
+    let consumers: Vec<_> = (0..2)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                stack.pop()
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    thread::sleep(Duration::from_millis(50));
+    stack.close();
+
+    for consumer in consumers {
+        assert_eq!(consumer.join().unwrap(), Err(StackError::Closed));
+    }
+}
+
+#[test]
+fn close_wakes_blocked_producers() {
+    let stack = Arc::new(BlockingStack::new(1));
+    stack.push(0).unwrap();
+    let barrier = Arc::new(Barrier::new(3));
+
+    let producers: Vec<_> = (1..=2)
+        .map(|item| {
+            let stack = Arc::clone(&stack);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                stack.push(item)
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    thread::sleep(Duration::from_millis(50));
+    stack.close();
+
+    for producer in producers {
+        assert_eq!(producer.join().unwrap(), Err(StackError::Closed));
+    }
+    assert_eq!(stack.pop(), Ok(0));
+    assert_eq!(stack.pop(), Err(StackError::Closed));
+}
@@ -0,0 +1,34 @@
+//! Fixtures shared by the integration tests.
+
+// Each test crate includes this module but only uses some of it.
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+
+/// Counts its drops in a shared counter.
+#[derive(Debug, Clone)]
+pub struct Counted<'a>(pub &'a Cell<usize>);
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+/// Returns a path in the temporary directory for the test called `name`,
+/// with nothing left at it from an earlier run.
+pub fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("stack-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Returns an empty directory for the test called `name`.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
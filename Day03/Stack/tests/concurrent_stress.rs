@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use rust::{ConcurrentStack, StackError};
+
+const THREADS: usize = 8;
+const PER_THREAD: usize = 20_000;
+
+#[test]
+fn single_thread_is_last_in_first_out() {
+    let stack = ConcurrentStack::new();
+    for value in 1..=3 {
+        stack.push(value);
+    }
+
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.pop(), Ok(3));
+    assert_eq!(stack.pop(), Ok(2));
+    assert_eq!(stack.pop(), Ok(1));
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn concurrent_pushes_and_pops_lose_and_duplicate_nothing() {
+    let stack = Arc::new(ConcurrentStack::new());
+    let barrier = Arc::new(Barrier::new(THREADS * 2));
+    let pushers_done = Arc::new(AtomicUsize::new(0));
+
+    let pushers: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let stack = Arc::clone(&stack);
+            let barrier = Arc::clone(&barrier);
+            let pushers_done = Arc::clone(&pushers_done);
+            thread::spawn(move || {
+                barrier.wait();
+                for i in 0..PER_THREAD {
+                    stack.push(thread * PER_THREAD + i);
+                }
+                pushers_done.fetch_add(1, Ordering::SeqCst);
+            })
+        })
+        .collect();
+
+    let poppers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            let barrier = Arc::clone(&barrier);
+            let pushers_done = Arc::clone(&pushers_done);
+            thread::spawn(move || {
+                barrier.wait();
+                let mut popped = Vec::new();
+                loop {
+                    match stack.pop() {
+                        Ok(value) => popped.push(value),
+                        Err(_) if pushers_done.load(Ordering::SeqCst) == THREADS => break,
+                        Err(_) => thread::yield_now(),
+                    }
+                }
+                popped
+            })
+        })
+        .collect();
+
+    for pusher in pushers {
+        pusher.join().unwrap();
+    }
+
+    let mut seen = HashSet::new();
+    for popper in poppers {
+        for value in popper.join().unwrap() {
+            assert!(seen.insert(value), "{} was popped twice", value);
+        }
+    }
+    while let Ok(value) = stack.pop() {
+        assert!(seen.insert(value), "{} was popped twice", value);
+    }
+
+    assert_eq!(seen.len(), THREADS * PER_THREAD);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn interleaved_push_pop_keeps_every_element() {
+    let stack = Arc::new(ConcurrentStack::new());
+    let barrier = Arc::new(Barrier::new(THREADS));
+
+    let workers: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let stack = Arc::clone(&stack);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                let mut popped = Vec::new();
+                for i in 0..PER_THREAD {
+                    stack.push(thread * PER_THREAD + i);
+                    if i % 2 == 1 {
+                        popped.push(stack.pop().unwrap());
+                    }
+                }
+                popped
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for worker in workers {
+        for value in worker.join().unwrap() {
+            assert!(seen.insert(value), "{} was popped twice", value);
+        }
+    }
+    while let Ok(value) = stack.pop() {
+        assert!(seen.insert(value), "{} was popped twice", value);
+    }
+
+    assert_eq!(seen.len(), THREADS * PER_THREAD);
+}
+
+#[test]
+fn every_value_is_dropped_exactly_once() {
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let stack = Arc::new(ConcurrentStack::new());
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let workers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            let drops = Arc::clone(&drops);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                for i in 0..PER_THREAD / 4 {
+                    stack.push(Tracked(Arc::clone(&drops)));
+                    if i % 3 == 0 {
+                        drop(stack.pop());
+                    }
+                }
+                while !stop.load(Ordering::SeqCst) && stack.pop().is_ok() {}
+            })
+        })
+        .collect();
+
+    stop.store(true, Ordering::SeqCst);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    drop(stack);
+
+    assert_eq!(drops.load(Ordering::SeqCst), THREADS * (PER_THREAD / 4));
+}
+
+#[test]
+fn len_never_exceeds_the_elements_pushed() {
+    let stack = Arc::new(ConcurrentStack::new());
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let workers: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    stack.push(thread * PER_THREAD + i);
+                    stack.pop().unwrap();
+                }
+            })
+        })
+        .collect();
+
+    let watcher = {
+        let stack = Arc::clone(&stack);
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                let len = stack.len();
+                assert!(len <= THREADS, "len() was {}", len);
+            }
+        })
+    };
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    stop.store(true, Ordering::SeqCst);
+    watcher.join().unwrap();
+    assert_eq!(stack.len(), 0);
+}
@@ -0,0 +1,105 @@
+use rust::{Element, InputErrorKind, Radix};
+
+#[test]
+fn integers_parse_every_literal_form() {
+    assert_eq!(i32::parse_token("0xFF"), Ok(255));
+    assert_eq!(i32::parse_token("0o17"), Ok(15));
+    assert_eq!(i32::parse_token("0b1010"), Ok(10));
+    assert_eq!(i32::parse_token("1_000_000"), Ok(1_000_000));
+    assert_eq!(i32::parse_token("-0b1010"), Ok(-10));
+    assert_eq!(i32::parse_token("+42"), Ok(42));
+}
+
+#[test]
+fn signed_minimum_parses_in_every_radix() {
+    assert_eq!(i32::parse_token("-0x80000000"), Ok(i32::MIN));
+    assert_eq!(i32::parse_token("-2147483648"), Ok(i32::MIN));
+    assert_eq!(i8::parse_token("-0b1000_0000"), Ok(i8::MIN));
+}
+
+#[test]
+fn type_suffix_must_name_the_element_type() {
+    assert_eq!(u8::parse_token("0xFF_u8"), Ok(255));
+    assert_eq!(u8::parse_token("7u8"), Ok(7));
+    assert_eq!(u8::parse_token("0xFF_i8"), Err(InputErrorKind::Malformed));
+    assert_eq!(i32::parse_token("7u8"), Err(InputErrorKind::Malformed));
+}
+
+#[test]
+fn malformed_literals_are_refused() {
+    for token in ["", "-", "0x", "0x_", "_1", "12ab", "0b102", "1.5"] {
+        assert_eq!(
+            i32::parse_token(token),
+            Err(InputErrorKind::Malformed),
+            "{:?}",
+            token
+        );
+    }
+}
+
+#[test]
+fn overflow_is_out_of_range() {
+    assert_eq!(u8::parse_token("256"), Err(InputErrorKind::OutOfRange));
+    assert_eq!(u8::parse_token("0x1_00"), Err(InputErrorKind::OutOfRange));
+    assert_eq!(
+        i32::parse_token("0x80000000"),
+        Err(InputErrorKind::OutOfRange)
+    );
+    assert_eq!(
+        i32::parse_token("-0x80000001"),
+        Err(InputErrorKind::OutOfRange)
+    );
+    assert_eq!(
+        i64::parse_token("9223372036854775808"),
+        Err(InputErrorKind::OutOfRange)
+    );
+}
+
+#[test]
+fn negative_values_format_with_a_sign_and_prefix() {
+    assert_eq!(
+        (-255i32).to_radix_string(Radix::Hexadecimal),
+        Some("-0xff".to_string())
+    );
+    assert_eq!(
+        (-8i32).to_radix_string(Radix::Octal),
+        Some("-0o10".to_string())
+    );
+    assert_eq!(
+        (-5i32).to_radix_string(Radix::Binary),
+        Some("-0b101".to_string())
+    );
+    assert_eq!(
+        (-5i32).to_radix_string(Radix::Decimal),
+        Some("-5".to_string())
+    );
+    assert_eq!(
+        i8::MIN.to_radix_string(Radix::Hexadecimal),
+        Some("-0x80".to_string())
+    );
+}
+
+#[test]
+fn radix_strings_parse_back() {
+    for value in [i16::MIN, -300, -1, 0, 1, 300, i16::MAX] {
+        for radix in [
+            Radix::Binary,
+            Radix::Octal,
+            Radix::Decimal,
+            Radix::Hexadecimal,
+        ] {
+            let text = value.to_radix_string(radix).unwrap();
+            assert_eq!(i16::parse_token(&text), Ok(value), "{}", text);
+        }
+    }
+}
+
+#[test]
+fn non_integers_only_format_in_decimal() {
+    assert_eq!(
+        1.5f64.to_radix_string(Radix::Decimal),
+        Some("1.5".to_string())
+    );
+    assert_eq!(1.5f64.to_radix_string(Radix::Hexadecimal), None);
+    assert_eq!(f64::parse_token("0x10"), Err(InputErrorKind::Malformed));
+}
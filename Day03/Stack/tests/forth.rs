@@ -0,0 +1,117 @@
+use rust::{OverflowPolicy, Stack, StackError, Word};
+
+/// Every word, a stack it applies to, bottom first, and what it leaves.
+const EFFECTS: [(Word, &[i32], &[i32]); 12] = [
+    (Word::Dup, &[1, 2], &[1, 2, 2]),
+    (Word::Drop, &[1, 2], &[1]),
+    (Word::Swap, &[1, 2, 3], &[1, 3, 2]),
+    (Word::Over, &[1, 2, 3], &[1, 2, 3, 2]),
+    (Word::Rot, &[1, 2, 3, 4], &[1, 3, 4, 2]),
+    (Word::MinusRot, &[1, 2, 3, 4], &[1, 4, 2, 3]),
+    (Word::Nip, &[1, 2, 3], &[1, 3]),
+    (Word::Tuck, &[1, 2, 3], &[1, 3, 2, 3]),
+    (Word::Pick(2), &[1, 2, 3, 4], &[1, 2, 3, 4, 2]),
+    (Word::Roll(2), &[1, 2, 3, 4], &[1, 3, 4, 2]),
+    (Word::TwoDup, &[1, 2, 3], &[1, 2, 3, 2, 3]),
+    (Word::TwoSwap, &[0, 1, 2, 3, 4], &[0, 3, 4, 1, 2]),
+];
+
+fn stack_of(elements: &[i32], capacity: usize, policy: OverflowPolicy) -> Stack<i32> {
+    let mut stack = Stack::with_policy(capacity, policy);
+    for &element in elements {
+        stack.push(element).unwrap();
+    }
+    stack
+}
+
+#[test]
+fn every_word_has_its_stack_effect() {
+    for (word, before, after) in EFFECTS {
+        let mut stack = stack_of(before, 8, OverflowPolicy::Reject);
+
+        assert_eq!(stack.word(word), Ok(()), "{}", word);
+        assert_eq!(stack.as_slice(), after, "{}", word);
+    }
+}
+
+#[test]
+fn every_word_needs_enough_elements() {
+    for (word, _, _) in EFFECTS {
+        let needed = word.takes();
+        let elements: Vec<i32> = (1..needed as i32).collect();
+        let mut stack = stack_of(&elements, 8, OverflowPolicy::Reject);
+
+        assert_eq!(
+            stack.word(word),
+            Err(StackError::TooFewElements {
+                needed,
+                len: needed - 1
+            }),
+            "{}",
+            word
+        );
+        assert_eq!(stack.as_slice(), &elements[..], "{}", word);
+    }
+}
+
+#[test]
+fn words_that_grow_the_stack_overflow_when_it_is_full() {
+    for policy in [
+        OverflowPolicy::Reject,
+        OverflowPolicy::EvictBottom,
+        OverflowPolicy::OverwriteTop,
+    ] {
+        for (word, before, after) in EFFECTS {
+            let mut stack = stack_of(before, before.len(), policy);
+
+            if after.len() > before.len() {
+                assert_eq!(
+                    stack.word(word),
+                    Err(StackError::Overflow {
+                        capacity: before.len()
+                    }),
+                    "{} {:?}",
+                    word,
+                    policy
+                );
+                assert_eq!(stack.as_slice(), before, "{} {:?}", word, policy);
+            } else {
+                assert_eq!(stack.word(word), Ok(()), "{} {:?}", word, policy);
+                assert_eq!(stack.as_slice(), after, "{} {:?}", word, policy);
+            }
+        }
+    }
+}
+
+#[test]
+fn words_grow_a_full_stack_with_the_grow_policy() {
+    for (word, before, after) in EFFECTS {
+        let mut stack = stack_of(before, before.len(), OverflowPolicy::Grow);
+
+        assert_eq!(stack.word(word), Ok(()), "{}", word);
+        assert_eq!(stack.as_slice(), after, "{}", word);
+    }
+}
+
+#[test]
+fn a_rolled_back_word_leaves_the_stack_as_it_was() {
+    for (word, before, _) in EFFECTS {
+        let mut stack = stack_of(before, 8, OverflowPolicy::Reject);
+
+        stack.begin().unwrap();
+        stack.word(word).unwrap();
+        stack.rollback().unwrap();
+        assert_eq!(stack.as_slice(), before, "{}", word);
+    }
+}
+
+#[test]
+fn words_are_found_by_name() {
+    for (word, _, _) in EFFECTS {
+        match word {
+            Word::Pick(_) | Word::Roll(_) => assert_eq!(Word::from_name(word.name()), None),
+            _ => assert_eq!(Word::from_name(word.name()), Some(word)),
+        }
+    }
+    assert_eq!(Word::Pick(3).to_string(), "pick 3");
+}
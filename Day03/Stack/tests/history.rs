@@ -0,0 +1,104 @@
+use rust::{History, Op, Outcome, OverflowPolicy, Stack, StackError, Word};
+
+/// Checks `history` rebuilds `live` as it is after the last event.
+fn assert_replays(history: &History<i32>, live: &Stack<i32>) {
+    let replayed = history.replay_to(history.last_seq()).unwrap();
+
+    assert_eq!(replayed.as_slice(), live.as_slice());
+    assert_eq!(replayed.capacity(), live.capacity());
+    assert_eq!(replayed.in_transaction(), live.in_transaction());
+}
+
+#[test]
+fn replay_matches_the_live_stack_after_every_event() {
+    let mut live = Stack::with_policy(4, OverflowPolicy::Reject);
+    live.push(7).unwrap();
+    let mut history = History::new(&live);
+
+    let ops = vec![
+        Op::Push(1),
+        Op::PushBatch(vec![2, 3, 4, 5]),
+        Op::Word(Word::Swap),
+        Op::Begin,
+        Op::Pop,
+        Op::Savepoint,
+        Op::Push(8),
+        Op::Commit,
+        Op::Begin,
+        Op::Word(Word::Drop),
+        Op::Rollback,
+        Op::Clear,
+        Op::Pop,
+    ];
+    let mut checkpoints = Vec::new();
+    for op in ops {
+        let _ = history.apply(&mut live, op);
+        assert_replays(&history, &live);
+        checkpoints.push(live.as_slice().to_vec());
+    }
+    assert!(history.get(2).unwrap().result.is_err());
+    assert_eq!(history.get(13).unwrap().result, Err(StackError::Underflow));
+
+    for (index, checkpoint) in checkpoints.iter().enumerate() {
+        let seq = history.first_seq() + index as u64;
+        assert_eq!(history.replay_to(seq).unwrap().as_slice(), &checkpoint[..]);
+    }
+}
+
+#[test]
+fn replay_follows_rollbacks_to_savepoints() {
+    let mut live = Stack::with_capacity(8);
+    let mut history = History::new(&live);
+
+    history.apply(&mut live, Op::Push(1)).unwrap();
+    history.apply(&mut live, Op::Begin).unwrap();
+    history.apply(&mut live, Op::Push(2)).unwrap();
+    let savepoint = match history.apply(&mut live, Op::Savepoint) {
+        Ok(Outcome::Savepoint(savepoint)) => savepoint,
+        other => panic!("expected a savepoint, got {:?}", other),
+    };
+    history.apply(&mut live, Op::Push(3)).unwrap();
+    history.apply(&mut live, Op::RollbackTo(savepoint)).unwrap();
+    assert_replays(&history, &live);
+    assert_eq!(live.as_slice(), &[1, 2]);
+
+    history.apply(&mut live, Op::Push(4)).unwrap();
+    history.apply(&mut live, Op::RollbackTo(savepoint)).unwrap();
+    history.apply(&mut live, Op::Commit).unwrap();
+    assert_replays(&history, &live);
+    assert_eq!(history.replay_to(5).unwrap().as_slice(), &[1, 2, 3]);
+}
+
+#[test]
+fn replay_to_before_the_first_event_is_the_start() {
+    let mut live = Stack::with_capacity(4);
+    live.push(1).unwrap();
+    let mut history = History::new(&live);
+    history.apply(&mut live, Op::Push(2)).unwrap();
+
+    let start = history.replay_to(history.first_seq() - 1).unwrap();
+    assert_eq!(start.as_slice(), &[1]);
+    assert!(history.replay_to(history.last_seq() + 1).is_none());
+}
+
+#[test]
+fn rebase_keeps_counting_events() {
+    let mut live = Stack::with_capacity(4);
+    let mut history = History::new(&live);
+    history.apply(&mut live, Op::Push(1)).unwrap();
+    history.apply(&mut live, Op::Push(2)).unwrap();
+
+    let mut replacement = Stack::with_capacity(4);
+    replacement.push(9).unwrap();
+    live = replacement;
+    history.rebase(&live);
+    assert!(history.is_empty());
+    assert_eq!(history.first_seq(), 3);
+    assert!(history.get(2).is_none());
+    assert!(history.replay_to(1).is_none());
+    assert_eq!(history.replay_to(2).unwrap().as_slice(), &[9]);
+
+    history.apply(&mut live, Op::Push(3)).unwrap();
+    assert_eq!(history.get(3).unwrap().seq, 3);
+    assert_eq!(history.replay_to(3).unwrap().as_slice(), &[9, 3]);
+}
@@ -0,0 +1,70 @@
+use rust::{OverflowPolicy, Stack};
+
+fn stack_of(elements: &[i32]) -> Stack<i32> {
+    let mut stack = Stack::with_policy(elements.len(), OverflowPolicy::Reject);
+    for &element in elements {
+        stack.push(element).unwrap();
+    }
+    stack
+}
+
+#[test]
+fn iter_goes_from_top_to_bottom() {
+    let stack = stack_of(&[1, 2, 3]);
+
+    assert_eq!(stack.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+    assert_eq!((&stack).into_iter().len(), 3);
+    assert_eq!(stack.iter().rev().copied().collect::<Vec<_>>(), [1, 2, 3]);
+}
+
+#[test]
+fn iter_bottom_up_goes_from_bottom_to_top() {
+    let stack = stack_of(&[1, 2, 3]);
+
+    assert_eq!(
+        stack.iter_bottom_up().copied().collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+}
+
+#[test]
+fn into_iter_goes_from_top_to_bottom() {
+    let stack = stack_of(&[1, 2, 3]);
+
+    let mut elements = stack.into_iter();
+    assert_eq!(elements.len(), 3);
+    assert_eq!(elements.next(), Some(3));
+    assert_eq!(elements.next_back(), Some(1));
+    assert_eq!(elements.collect::<Vec<_>>(), [2]);
+}
+
+#[test]
+fn into_iter_follows_evictions() {
+    let mut stack = Stack::with_policy(3, OverflowPolicy::EvictBottom);
+    for number in 1..=5 {
+        stack.push(number).unwrap();
+    }
+
+    assert_eq!(stack.into_iter().collect::<Vec<_>>(), [5, 4, 3]);
+}
+
+#[test]
+fn drain_pops_from_top_to_bottom() {
+    let mut stack = stack_of(&[1, 2, 3]);
+
+    assert_eq!(stack.drain().collect::<Vec<_>>(), [3, 2, 1]);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn dropping_a_partial_drain_removes_the_rest() {
+    let mut stack = stack_of(&[1, 2, 3]);
+
+    let mut drain = stack.drain();
+    assert_eq!(drain.next(), Some(3));
+    drop(drain);
+    assert!(stack.is_empty());
+    assert_eq!(stack.capacity(), 3);
+    stack.push(4).unwrap();
+    assert_eq!(stack.as_slice(), &[4]);
+}
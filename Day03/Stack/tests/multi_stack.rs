@@ -0,0 +1,107 @@
+use std::cell::Cell;
+
+use rust::{MultiStack, StackError};
+
+mod common;
+
+use common::Counted;
+
+fn push_all(stacks: &mut MultiStack<i32>, stack: usize, elements: &[i32]) {
+    for &element in elements {
+        stacks.push(stack, element).unwrap();
+    }
+}
+
+#[test]
+fn a_full_stack_borrows_from_the_neighbour_above() {
+    let mut stacks = MultiStack::new(3, 9);
+    push_all(&mut stacks, 0, &[1, 2, 3]);
+    push_all(&mut stacks, 1, &[10]);
+
+    stacks.push(0, 4).unwrap();
+    assert_eq!(stacks.as_slice(0), &[1, 2, 3, 4]);
+    assert_eq!(stacks.as_slice(1), &[10]);
+    assert!(stacks.is_empty(2));
+
+    stacks.push(0, 5).unwrap();
+    assert_eq!(stacks.as_slice(0), &[1, 2, 3, 4, 5]);
+    assert_eq!(stacks.as_slice(1), &[10]);
+
+    // Stack 1 is now full too, so both shift into stack 2's region.
+    stacks.push(0, 6).unwrap();
+    assert_eq!(stacks.as_slice(0), &[1, 2, 3, 4, 5, 6]);
+    assert_eq!(stacks.as_slice(1), &[10]);
+    stacks.push(1, 11).unwrap();
+    assert_eq!(stacks.as_slice(1), &[10, 11]);
+    push_all(&mut stacks, 2, &[20]);
+    assert_eq!(stacks.as_slice(2), &[20]);
+    assert_eq!(stacks.total_len(), 9);
+}
+
+#[test]
+fn the_last_stack_borrows_from_the_neighbour_below() {
+    let mut stacks = MultiStack::new(3, 9);
+    push_all(&mut stacks, 0, &[1]);
+    push_all(&mut stacks, 2, &[20, 21, 22]);
+
+    stacks.push(2, 23).unwrap();
+    assert_eq!(stacks.as_slice(0), &[1]);
+    assert!(stacks.is_empty(1));
+    assert_eq!(stacks.as_slice(2), &[20, 21, 22, 23]);
+
+    push_all(&mut stacks, 1, &[10]);
+    stacks.push(2, 24).unwrap();
+    assert_eq!(stacks.as_slice(1), &[10]);
+    assert_eq!(stacks.as_slice(2), &[20, 21, 22, 23, 24]);
+
+    // Stack 1 is full now, so both shift into stack 0's region.
+    stacks.push(2, 25).unwrap();
+    assert_eq!(stacks.as_slice(0), &[1]);
+    assert_eq!(stacks.as_slice(1), &[10]);
+    assert_eq!(stacks.as_slice(2), &[20, 21, 22, 23, 24, 25]);
+    assert_eq!(stacks.peek(2), Some(&25));
+}
+
+#[test]
+fn a_full_buffer_overflows_without_changing_anything() {
+    let mut stacks = MultiStack::new(2, 4);
+    push_all(&mut stacks, 0, &[1, 2, 3]);
+    push_all(&mut stacks, 1, &[10]);
+
+    assert!(stacks.is_full());
+    assert_eq!(stacks.push(0, 4), Err(StackError::Overflow { capacity: 4 }));
+    assert_eq!(
+        stacks.push(1, 11),
+        Err(StackError::Overflow { capacity: 4 })
+    );
+    assert_eq!(stacks.as_slice(0), &[1, 2, 3]);
+    assert_eq!(stacks.as_slice(1), &[10]);
+
+    assert_eq!(stacks.pop(0), Ok(3));
+    stacks.push(1, 11).unwrap();
+    assert_eq!(stacks.as_slice(1), &[10, 11]);
+}
+
+#[test]
+fn clear_and_drop_drop_every_element_once() {
+    let drops = Cell::new(0);
+    let mut stacks = MultiStack::new(3, 6);
+    for stack in 0..3 {
+        stacks.push(stack, Counted(&drops)).unwrap();
+    }
+    // Shifting regions moves elements without dropping them.
+    stacks.push(0, Counted(&drops)).unwrap();
+    stacks.push(0, Counted(&drops)).unwrap();
+    stacks.push(2, Counted(&drops)).unwrap();
+    assert_eq!(drops.get(), 0);
+
+    stacks.clear(0);
+    assert_eq!(drops.get(), 3);
+    assert!(stacks.is_empty(0));
+
+    drop(stacks.pop(2).unwrap());
+    assert_eq!(drops.get(), 4);
+
+    drop(stacks);
+    assert_eq!(drops.get(), 6);
+}
@@ -0,0 +1,205 @@
+use rust::parse::parse_batch;
+use rust::{InputErrorKind, OverflowPolicy, Stack, StackError};
+
+fn full_stack(policy: OverflowPolicy) -> Stack<i32> {
+    let mut stack = Stack::with_policy(3, policy);
+    for number in 1..=3 {
+        stack.push(number).unwrap();
+    }
+    stack
+}
+
+#[test]
+fn reject_refuses_push_when_full() {
+    let mut stack = full_stack(OverflowPolicy::Reject);
+
+    assert_eq!(stack.push(4), Err(StackError::Overflow { capacity: 3 }));
+    assert_eq!(stack.as_slice(), &[1, 2, 3]);
+    assert_eq!(stack.capacity(), 3);
+}
+
+#[test]
+fn grow_keeps_every_element() {
+    let mut stack = full_stack(OverflowPolicy::Grow);
+
+    assert_eq!(stack.push_displacing(4), Ok(None));
+    assert_eq!(stack.as_slice(), &[1, 2, 3, 4]);
+    assert!(stack.capacity() >= 4);
+}
+
+#[test]
+fn grow_starts_from_zero_capacity() {
+    let mut stack = Stack::with_policy(0, OverflowPolicy::Grow);
+
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.as_slice(), &[1, 2]);
+}
+
+#[test]
+fn evict_bottom_keeps_most_recent_elements() {
+    let mut stack = full_stack(OverflowPolicy::EvictBottom);
+
+    assert_eq!(stack.push_displacing(4), Ok(Some(1)));
+    assert_eq!(stack.push_displacing(5), Ok(Some(2)));
+    assert_eq!(stack.as_slice(), &[3, 4, 5]);
+    assert_eq!(stack.capacity(), 3);
+    assert_eq!(stack.pop(), Ok(5));
+}
+
+#[test]
+fn evict_bottom_keeps_elements_in_one_slice() {
+    let mut stack = full_stack(OverflowPolicy::EvictBottom);
+
+    for number in 4..=100 {
+        stack.push(number).unwrap();
+        assert_eq!(stack.as_slice(), &[number - 2, number - 1, number]);
+    }
+    stack.begin().unwrap();
+    for number in 101..=110 {
+        stack.push(number).unwrap();
+    }
+    stack.rollback().unwrap();
+    assert_eq!(stack.as_slice(), &[98, 99, 100]);
+}
+
+#[test]
+fn overwrite_top_replaces_the_top_element() {
+    let mut stack = full_stack(OverflowPolicy::OverwriteTop);
+
+    assert_eq!(stack.push_displacing(4), Ok(Some(3)));
+    assert_eq!(stack.as_slice(), &[1, 2, 4]);
+    assert_eq!(stack.peek(), Some(&4));
+}
+
+#[test]
+fn displacing_policies_overflow_without_capacity() {
+    for policy in [OverflowPolicy::EvictBottom, OverflowPolicy::OverwriteTop] {
+        let mut stack = Stack::with_policy(0, policy);
+
+        assert_eq!(stack.push(1), Err(StackError::Overflow { capacity: 0 }));
+        assert!(stack.is_empty());
+    }
+}
+
+#[test]
+fn rolling_back_evictions_restores_the_bottom() {
+    let mut stack = full_stack(OverflowPolicy::EvictBottom);
+
+    stack.begin().unwrap();
+    for number in 4..=9 {
+        stack.push(number).unwrap();
+    }
+    assert_eq!(stack.as_slice(), &[7, 8, 9]);
+    stack.rollback().unwrap();
+    assert_eq!(stack.as_slice(), &[1, 2, 3]);
+    assert_eq!(stack.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+}
+
+#[test]
+fn rolling_back_growth_restores_the_capacity() {
+    let mut stack = full_stack(OverflowPolicy::Grow);
+
+    stack.begin().unwrap();
+    let savepoint = stack.savepoint().unwrap();
+    stack.push(4).unwrap();
+    stack.push(5).unwrap();
+    stack.rollback_to(savepoint).unwrap();
+    assert_eq!(stack.capacity(), 3);
+    stack.check_invariants();
+
+    stack.push(4).unwrap();
+    stack.drain().for_each(drop);
+    stack.rollback().unwrap();
+    assert_eq!(stack.as_slice(), &[1, 2, 3]);
+    assert_eq!(stack.capacity(), 3);
+    stack.check_invariants();
+}
+
+#[test]
+fn collected_stacks_grow() {
+    let mut stack: Stack<i32> = (1..=3).collect();
+
+    assert_eq!(stack.policy(), OverflowPolicy::Grow);
+    stack.extend([4, 5].iter().copied());
+    assert_eq!(stack.as_slice(), &[1, 2, 3, 4, 5]);
+}
+
+#[test]
+#[should_panic(expected = "cannot extend the stack")]
+fn extend_panics_when_the_policy_refuses() {
+    let mut stack = full_stack(OverflowPolicy::Reject);
+
+    stack.extend(Some(4));
+}
+
+#[test]
+fn push_batch_reports_every_overflowing_position() {
+    let mut stack = Stack::with_policy(5, OverflowPolicy::Reject);
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+
+    let err = stack.push_batch(vec![3, 4, 5, 6, 7]).unwrap_err();
+    let positions: Vec<usize> = err.rejected.iter().map(|token| token.position).collect();
+    assert_eq!(positions, [4, 5]);
+    for token in &err.rejected {
+        assert_eq!(token.error, StackError::Overflow { capacity: 5 });
+    }
+    assert_eq!(stack.as_slice(), &[1, 2]);
+
+    stack.push_batch(vec![3, 4, 5]).unwrap();
+    assert_eq!(stack.as_slice(), &[1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn parse_batch_reports_every_invalid_token() {
+    let err = parse_batch::<i32>("1 x 3 99999999999").unwrap_err();
+    let rejected: Vec<(usize, StackError)> = err
+        .rejected
+        .iter()
+        .map(|token| (token.position, token.error))
+        .collect();
+    assert_eq!(
+        rejected,
+        [
+            (
+                2,
+                StackError::InvalidInput {
+                    position: 2,
+                    kind: InputErrorKind::Malformed
+                }
+            ),
+            (
+                4,
+                StackError::InvalidInput {
+                    position: 4,
+                    kind: InputErrorKind::OutOfRange
+                }
+            ),
+        ]
+    );
+    assert_eq!(parse_batch::<i32>("1 2 3"), Ok(vec![1, 2, 3]));
+}
+
+#[test]
+fn room_counts_what_the_policy_accepts() {
+    let mut stack = Stack::with_policy(3, OverflowPolicy::Reject);
+    stack.push(1).unwrap();
+    assert_eq!(stack.room(), Some(2));
+
+    for policy in [
+        OverflowPolicy::Grow,
+        OverflowPolicy::EvictBottom,
+        OverflowPolicy::OverwriteTop,
+    ] {
+        assert_eq!(full_stack(policy).room(), None, "{:?}", policy);
+    }
+    assert_eq!(
+        Stack::<i32>::with_policy(0, OverflowPolicy::EvictBottom).room(),
+        Some(0)
+    );
+    assert_eq!(
+        Stack::<i32>::with_policy(0, OverflowPolicy::Grow).room(),
+        None
+    );
+}
@@ -0,0 +1,65 @@
+use rust::{PersistentStack, StackError};
+
+fn elements(stack: &PersistentStack<i32>) -> Vec<i32> {
+    stack.iter().copied().collect()
+}
+
+#[test]
+fn pushing_onto_an_old_version_leaves_newer_ones_alone() {
+    let base: PersistentStack<i32> = (1..=3).collect();
+    let newer = base.push(4);
+
+    let branch = base.push(10);
+    assert_eq!(elements(&base), [3, 2, 1]);
+    assert_eq!(elements(&newer), [4, 3, 2, 1]);
+    assert_eq!(elements(&branch), [10, 3, 2, 1]);
+    assert_eq!(newer.len(), 4);
+    assert_eq!(branch.len(), 4);
+}
+
+#[test]
+fn popping_an_old_version_leaves_newer_ones_alone() {
+    let base: PersistentStack<i32> = (1..=3).collect();
+    let newer = base.push(4);
+
+    let (top, below) = base.pop().unwrap();
+    assert_eq!(*top, 3);
+    assert_eq!(elements(&below), [2, 1]);
+    assert_eq!(elements(&base), [3, 2, 1]);
+    assert_eq!(elements(&newer), [4, 3, 2, 1]);
+
+    let (top, below) = newer.pop().unwrap();
+    assert_eq!(*top, 4);
+    assert!(below.ptr_eq(&base));
+}
+
+#[test]
+fn popping_an_empty_version_underflows() {
+    let empty = PersistentStack::<i32>::new();
+
+    assert_eq!(empty.pop().err(), Some(StackError::Underflow));
+    assert!(empty.ptr_eq(&PersistentStack::default()));
+}
+
+#[test]
+fn clones_share_their_cells() {
+    let stack: PersistentStack<i32> = (1..=3).collect();
+    let clone = stack.clone();
+
+    assert!(stack.ptr_eq(&clone));
+    assert!(!stack.ptr_eq(&clone.push(4)));
+    assert!(!stack.ptr_eq(&(1..=3).collect()));
+}
+
+#[test]
+fn dropping_a_deep_stack_does_not_overflow() {
+    let base: PersistentStack<i32> = (0..1_000_000).collect();
+    let first = base.push(-1);
+    let second = base.push(-2);
+
+    drop(base);
+    drop(first);
+    assert_eq!(second.len(), 1_000_001);
+    assert_eq!(second.iter().nth(1_000_000), Some(&0));
+    drop(second);
+}
@@ -0,0 +1,72 @@
+use rust::{SegmentedStack, StackError};
+
+fn filled(segment_len: usize, count: i32) -> SegmentedStack<i32> {
+    let mut stack = SegmentedStack::with_segment_len(segment_len);
+    for value in 0..count {
+        stack.push(value);
+    }
+    stack
+}
+
+#[test]
+fn pops_are_last_in_first_out_across_segments() {
+    let mut stack = filled(3, 10);
+
+    assert_eq!(stack.len(), 10);
+    assert_eq!(stack.segment_count(), 4);
+    for expected in (0..10).rev() {
+        assert_eq!(stack.peek(), Some(&expected));
+        assert_eq!(stack.pop(), Ok(expected));
+    }
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn crossing_a_boundary_reuses_the_spare_segment() {
+    let mut stack = filled(3, 3);
+    assert_eq!(stack.segment_count(), 1);
+
+    stack.push(3);
+    assert_eq!(stack.segment_count(), 2);
+    let top = stack.peek().unwrap() as *const i32;
+
+    for _ in 0..3 {
+        assert_eq!(stack.pop(), Ok(3));
+        assert_eq!(stack.segment_count(), 1);
+        stack.push(3);
+        assert_eq!(stack.segment_count(), 2);
+        assert_eq!(stack.peek().unwrap() as *const i32, top);
+    }
+}
+
+#[test]
+fn clear_empties_every_segment() {
+    let mut stack = filled(3, 7);
+
+    stack.clear();
+    assert!(stack.is_empty());
+    assert_eq!(stack.segment_count(), 0);
+    assert_eq!(stack.peek(), None);
+    assert_eq!(stack.iter().count(), 0);
+
+    stack.push(1);
+    assert_eq!(stack.segment_count(), 1);
+    assert_eq!(stack.pop(), Ok(1));
+}
+
+#[test]
+fn iter_goes_from_top_to_bottom() {
+    let stack = filled(3, 8);
+
+    assert_eq!(
+        stack.iter().copied().collect::<Vec<_>>(),
+        (0..8).rev().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[should_panic(expected = "segments must hold at least one element")]
+fn zero_length_segments_are_refused() {
+    SegmentedStack::<i32>::with_segment_len(0);
+}
@@ -0,0 +1,128 @@
+use std::fs;
+
+use rust::snapshot::{self, Format, SnapshotError, VERSION};
+use rust::{OverflowPolicy, Stack};
+
+mod common;
+
+fn strings() -> Stack<String> {
+    let mut stack = Stack::with_policy(5, OverflowPolicy::EvictBottom);
+    for element in [
+        "plain",
+        "back\\slash",
+        "two\nlines",
+        "carriage\rreturn",
+        "\\n\r\n",
+    ] {
+        stack.push(element.to_string()).unwrap();
+    }
+    stack
+}
+
+fn binary<T: rust::Codec>(stack: &Stack<T>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    snapshot::write_binary(stack, &mut bytes).unwrap();
+    bytes
+}
+
+fn text<T: rust::Codec + rust::Element>(stack: &Stack<T>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    snapshot::write_text(stack, &mut bytes).unwrap();
+    bytes
+}
+
+fn assert_same(read: Stack<String>, written: Stack<String>) {
+    assert_eq!(read.capacity(), written.capacity());
+    assert_eq!(read.policy(), written.policy());
+    assert_eq!(read.into_vec(), written.into_vec());
+}
+
+#[test]
+fn binary_round_trip_keeps_every_string() {
+    let read = snapshot::read_binary(&binary(&strings())[..]).unwrap();
+    assert_same(read, strings());
+}
+
+#[test]
+fn text_round_trip_keeps_escaped_characters() {
+    let bytes = text(&strings());
+    assert_eq!(std::str::from_utf8(&bytes).unwrap().lines().count(), 11);
+
+    let read = snapshot::read_text(&bytes[..]).unwrap();
+    assert_same(read, strings());
+}
+
+#[test]
+fn save_and_load_round_trip_through_a_file() {
+    let path = common::temp_path("snapshot");
+    for format in [Format::Binary, Format::Text] {
+        snapshot::save(&strings(), &path, format).unwrap();
+        assert_same(snapshot::load(&path).unwrap(), strings());
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn a_flipped_byte_fails_the_checksum() {
+    let mut bytes = binary(&strings());
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0x01;
+    assert!(matches!(
+        snapshot::read_binary::<String, _>(&bytes[..]),
+        Err(SnapshotError::ChecksumMismatch)
+    ));
+
+    let mut bytes = text(&strings());
+    let plain = bytes.windows(5).position(|word| word == b"plain").unwrap();
+    bytes[plain] = b'q';
+    assert!(matches!(
+        snapshot::read_text::<String, _>(&bytes[..]),
+        Err(SnapshotError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn another_element_type_is_refused() {
+    let numbers: Stack<i32> = (1..=3).collect();
+
+    assert!(matches!(
+        snapshot::read_binary::<String, _>(&binary(&numbers)[..]),
+        Err(SnapshotError::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        snapshot::read_text::<String, _>(&text(&numbers)[..]),
+        Err(SnapshotError::TypeMismatch { .. })
+    ));
+}
+
+#[test]
+fn a_newer_version_is_refused() {
+    let newer = VERSION + 1;
+
+    let mut bytes = binary(&strings());
+    bytes[4..6].copy_from_slice(&newer.to_le_bytes());
+    assert!(matches!(
+        snapshot::read_binary::<String, _>(&bytes[..]),
+        Err(SnapshotError::UnsupportedVersion(version)) if version == newer
+    ));
+
+    let written = String::from_utf8(text(&strings())).unwrap();
+    let bytes = written.replacen(&format!("v{}\n", VERSION), &format!("v{}\n", newer), 1);
+    assert!(matches!(
+        snapshot::read_text::<String, _>(bytes.as_bytes()),
+        Err(SnapshotError::UnsupportedVersion(version)) if version == newer
+    ));
+}
+
+#[test]
+fn a_truncated_file_is_refused() {
+    let bytes = binary(&strings());
+    for len in 0..bytes.len() {
+        assert!(snapshot::read_binary::<String, _>(&bytes[..len]).is_err());
+    }
+
+    let bytes = text(&strings());
+    for len in 0..bytes.len() {
+        assert!(snapshot::read_text::<String, _>(&bytes[..len]).is_err());
+    }
+}
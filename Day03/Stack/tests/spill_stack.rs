@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rust::SpillStack;
+
+mod common;
+
+/// Returns the total size of the files in `dir`.
+fn spilled_bytes(dir: &PathBuf) -> u64 {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().metadata().unwrap().len())
+        .sum()
+}
+
+#[test]
+fn spilled_pages_come_back_in_order() {
+    let dir = common::temp_dir("spill-reload");
+    let mut stack = SpillStack::new_in(&dir, 100, 4);
+
+    for number in 1..=20 {
+        stack.push(number).unwrap();
+        assert!(stack.resident_len() <= 4);
+    }
+    assert_eq!(stack.len(), 20);
+    assert!(stack.spilled_len() > 0);
+    assert!(spilled_bytes(&dir) > 0);
+
+    for number in (1..=20).rev() {
+        assert_eq!(stack.peek(), Some(&number));
+        assert_eq!(stack.pop().unwrap(), number);
+    }
+    assert_eq!(stack.peek(), None);
+    assert!(stack.pop().is_err());
+    assert_eq!(spilled_bytes(&dir), 0);
+
+    drop(stack);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_single_resident_element_stays_alone_in_memory() {
+    let dir = common::temp_dir("spill-single");
+    let mut stack = SpillStack::new_in(&dir, 10, 1);
+
+    for number in 1..=5 {
+        stack.push(number).unwrap();
+        assert_eq!(stack.resident_len(), 1);
+    }
+    for number in (1..=5).rev() {
+        assert_eq!(stack.pop().unwrap(), number);
+        assert!(stack.resident_len() <= 1);
+        assert_eq!(
+            stack.spilled_len() + stack.resident_len(),
+            number as usize - 1
+        );
+    }
+
+    drop(stack);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn peek_sees_the_top_after_pops_reach_a_page() {
+    let dir = common::temp_dir("spill-peek");
+    let mut stack = SpillStack::new_in(&dir, 10, 2);
+
+    for number in 1..=5 {
+        stack.push(number).unwrap();
+    }
+    while stack.spilled_len() > 0 {
+        stack.pop().unwrap();
+        assert_eq!(stack.peek().copied(), Some(stack.len() as i32));
+    }
+
+    drop(stack);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn clear_empties_memory_and_the_spill_file() {
+    let dir = common::temp_dir("spill-clear");
+    let mut stack = SpillStack::new_in(&dir, 100, 4);
+    for number in 1..=20 {
+        stack.push(number).unwrap();
+    }
+
+    stack.clear().unwrap();
+    assert!(stack.is_empty());
+    assert_eq!(stack.spilled_len(), 0);
+    assert_eq!(stack.peek(), None);
+    assert_eq!(spilled_bytes(&dir), 0);
+
+    for number in 1..=10 {
+        stack.push(number).unwrap();
+    }
+    assert!(stack.spilled_len() > 0);
+    for number in (1..=10).rev() {
+        assert_eq!(stack.pop().unwrap(), number);
+    }
+
+    drop(stack);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,114 @@
+use rust::{MinMaxStack, Op, OverflowPolicy, Stack, StackError, Summary, Total, Word};
+
+/// Applies `op` to `stack` and checks `summary` still agrees with a scan.
+fn apply(stack: &mut Stack<i32>, summary: &mut Summary<i32>, op: Op<i32>) {
+    let unchanged = Summary::unchanged_by(stack, &op);
+    let _ = stack.apply(op);
+    summary.sync(stack, unchanged);
+
+    assert_eq!(summary.min(), stack.iter().min());
+    assert_eq!(summary.max(), stack.iter().max());
+    let sum = stack.iter().map(|&value| i128::from(value)).sum::<i128>();
+    assert_eq!(
+        summary.sum(),
+        Some(Total::Signed(sum)).filter(|_| !stack.is_empty())
+    );
+}
+
+#[test]
+fn summary_follows_every_kind_of_op() {
+    for policy in [
+        OverflowPolicy::Reject,
+        OverflowPolicy::Grow,
+        OverflowPolicy::EvictBottom,
+        OverflowPolicy::OverwriteTop,
+    ] {
+        let mut stack = Stack::with_policy(4, policy);
+        let mut summary = Summary::new(&stack);
+        let ops = vec![
+            Op::Push(5),
+            Op::PushBatch(vec![1, 9, 3]),
+            Op::Begin,
+            Op::Push(-2),
+            Op::Word(Word::Rot),
+            Op::Word(Word::Roll(3)),
+            Op::Savepoint,
+            Op::Pop,
+            Op::Word(Word::Dup),
+            Op::Rollback,
+            Op::Word(Word::Nip),
+            Op::PushBatch(vec![7, 0]),
+            Op::Word(Word::TwoSwap),
+            Op::Clear,
+            Op::Push(4),
+        ];
+        for op in ops {
+            apply(&mut stack, &mut summary, op);
+        }
+    }
+}
+
+#[test]
+fn mean_divides_by_the_length() {
+    let stack: Stack<i32> = vec![1, 2, 6].into_iter().collect();
+    let summary = Summary::new(&stack);
+
+    assert_eq!(summary.mean(), Some(3.0));
+}
+
+#[test]
+fn integer_sums_are_exact() {
+    let stack: Stack<i64> = vec![9_007_199_254_740_993, 0].into_iter().collect();
+    let summary = Summary::new(&stack);
+    assert_eq!(summary.sum(), Some(Total::Signed(9_007_199_254_740_993)));
+    assert_eq!(summary.sum().unwrap().to_string(), "9007199254740993");
+
+    let stack: Stack<u64> = vec![u64::MAX, 1].into_iter().collect();
+    let summary = Summary::new(&stack);
+    assert_eq!(summary.sum().unwrap().to_string(), "18446744073709551616");
+}
+
+#[test]
+fn widest_integer_sums_fall_back_to_floats() {
+    let stack: Stack<i128> = vec![i128::MAX, 1].into_iter().collect();
+
+    assert_eq!(
+        Summary::new(&stack).sum(),
+        Some(Total::Float(i128::MAX as f64 + 1.0))
+    );
+}
+
+#[test]
+fn non_numeric_elements_have_no_sum() {
+    let stack: Stack<String> = vec!["b".to_string(), "a".to_string()].into_iter().collect();
+    let summary = Summary::new(&stack);
+
+    assert_eq!(summary.min().map(String::as_str), Some("a"));
+    assert_eq!(summary.sum(), None);
+    assert_eq!(summary.mean(), None);
+}
+
+#[test]
+fn min_max_stack_reveals_the_summary_below_on_pop() {
+    let mut stack = MinMaxStack::with_capacity(4);
+    for value in [5, 1, 9, 3] {
+        stack.push(value).unwrap();
+    }
+
+    assert_eq!(stack.push(0), Err(StackError::Overflow { capacity: 4 }));
+    assert_eq!((stack.min(), stack.max()), (Some(&1), Some(&9)));
+    assert_eq!(stack.sum(), Some(Total::Signed(18)));
+    assert_eq!(stack.mean(), Some(4.5));
+
+    assert_eq!(stack.pop(), Ok(3));
+    assert_eq!(stack.pop(), Ok(9));
+    assert_eq!((stack.min(), stack.max()), (Some(&1), Some(&5)));
+    assert_eq!(stack.sum(), Some(Total::Signed(6)));
+    assert_eq!(stack.peek(), Some(&1));
+    assert_eq!(stack.iter().copied().collect::<Vec<_>>(), [1, 5]);
+
+    stack.clear();
+    assert!(stack.is_empty());
+    assert_eq!((stack.min(), stack.sum(), stack.mean()), (None, None, None));
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+}
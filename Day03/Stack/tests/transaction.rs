@@ -0,0 +1,64 @@
+use std::fs;
+
+use rust::wal::WalOptions;
+use rust::{DurableStack, Op, Outcome, OverflowPolicy, Stack, StackError};
+
+mod common;
+
+#[test]
+fn savepoints_of_an_earlier_transaction_are_refused() {
+    let mut stack = Stack::with_capacity(4);
+
+    stack.begin().unwrap();
+    let old = stack.savepoint().unwrap();
+    stack.commit().unwrap();
+
+    stack.begin().unwrap();
+    let current = stack.savepoint().unwrap();
+    assert_eq!(old.id(), current.id());
+    stack.push(1).unwrap();
+
+    assert_eq!(stack.rollback_to(old), Err(StackError::UnknownSavepoint));
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.rollback_to(current), Ok(()));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn savepoints_do_not_survive_a_rollback() {
+    let mut stack = Stack::<i32>::with_capacity(4);
+
+    stack.begin().unwrap();
+    let old = stack.savepoint().unwrap();
+    stack.rollback().unwrap();
+
+    stack.begin().unwrap();
+    stack.savepoint().unwrap();
+    assert_eq!(stack.rollback_to(old), Err(StackError::UnknownSavepoint));
+}
+
+#[test]
+fn replaying_a_log_refuses_savepoints_from_before_its_snapshot() {
+    let dir = common::temp_dir("savepoints");
+    let open = || DurableStack::<i32>::open(&dir, 4, OverflowPolicy::Reject, WalOptions::default());
+
+    let mut durable = open().unwrap();
+    durable.apply(Op::Begin).unwrap();
+    let old = match durable.apply(Op::Savepoint).unwrap() {
+        Outcome::Savepoint(savepoint) => savepoint,
+        _ => unreachable!(),
+    };
+    durable.apply(Op::Commit).unwrap();
+    durable.compact().unwrap();
+
+    durable.apply(Op::Begin).unwrap();
+    durable.apply(Op::Savepoint).unwrap();
+    durable.push(1).unwrap();
+    assert!(durable.apply(Op::RollbackTo(old)).is_err());
+    durable.apply(Op::Commit).unwrap();
+    drop(durable);
+
+    let reopened = open().unwrap();
+    assert_eq!(reopened.stack().iter_bottom_up().collect::<Vec<_>>(), [&1]);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,109 @@
+use rust::{Command, StackError, UndoManager};
+
+/// Appends its value to a list of numbers.
+struct Append(i32);
+
+impl Command for Append {
+    type Target = Vec<i32>;
+
+    fn apply(&mut self, target: &mut Vec<i32>) {
+        target.push(self.0);
+    }
+
+    fn revert(&mut self, target: &mut Vec<i32>) {
+        assert_eq!(target.pop(), Some(self.0));
+    }
+}
+
+#[test]
+fn undo_and_redo_walk_the_history() {
+    let mut history = UndoManager::with_limit(10);
+    let mut list = Vec::new();
+    history.execute(Append(1), &mut list);
+    history.execute(Append(2), &mut list);
+
+    history.undo(&mut list).unwrap();
+    assert_eq!(list, [1]);
+    history.undo(&mut list).unwrap();
+    assert!(list.is_empty());
+    assert_eq!(history.undo(&mut list), Err(StackError::Underflow));
+
+    history.redo(&mut list).unwrap();
+    history.redo(&mut list).unwrap();
+    assert_eq!(list, [1, 2]);
+    assert_eq!(history.redo(&mut list), Err(StackError::Underflow));
+}
+
+#[test]
+fn the_limit_evicts_the_oldest_step() {
+    let mut history = UndoManager::with_limit(2);
+    let mut list = Vec::new();
+    for value in 1..=3 {
+        history.execute(Append(value), &mut list);
+    }
+
+    assert_eq!(history.undo_len(), 2);
+    history.undo(&mut list).unwrap();
+    history.undo(&mut list).unwrap();
+    assert_eq!(list, [1]);
+    assert_eq!(history.undo(&mut list), Err(StackError::Underflow));
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn nested_groups_make_one_step() {
+    let mut history = UndoManager::with_limit(10);
+    let mut list = Vec::new();
+    history.execute(Append(1), &mut list);
+
+    history.begin_group();
+    history.execute(Append(2), &mut list);
+    history.begin_group();
+    history.execute(Append(3), &mut list);
+    history.end_group();
+    history.execute(Append(4), &mut list);
+    history.end_group();
+
+    assert_eq!(history.undo_len(), 2);
+    history.undo(&mut list).unwrap();
+    assert_eq!(list, [1]);
+    history.redo(&mut list).unwrap();
+    assert_eq!(list, [1, 2, 3, 4]);
+}
+
+#[test]
+fn undo_closes_an_open_group() {
+    let mut history = UndoManager::with_limit(10);
+    let mut list = Vec::new();
+    history.execute(Append(1), &mut list);
+
+    history.begin_group();
+    history.begin_group();
+    history.execute(Append(2), &mut list);
+    history.execute(Append(3), &mut list);
+    assert!(history.can_undo());
+    history.undo(&mut list).unwrap();
+    assert_eq!(list, [1]);
+
+    // The group is closed, so the next command is a step of its own.
+    history.end_group();
+    history.execute(Append(4), &mut list);
+    history.undo(&mut list).unwrap();
+    assert_eq!(list, [1]);
+    assert_eq!(history.undo_len(), 1);
+}
+
+#[test]
+fn execute_discards_the_redo_stack() {
+    let mut history = UndoManager::with_limit(10);
+    let mut list = Vec::new();
+    history.execute(Append(1), &mut list);
+    history.execute(Append(2), &mut list);
+    history.undo(&mut list).unwrap();
+    assert!(history.can_redo());
+
+    history.execute(Append(3), &mut list);
+    assert!(!history.can_redo());
+    assert_eq!(history.redo(&mut list), Err(StackError::Underflow));
+    assert_eq!(list, [1, 3]);
+}
@@ -0,0 +1,183 @@
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use rust::snapshot;
+use rust::wal::{WalOptions, LOG_FILE, SNAPSHOT_FILE};
+use rust::{DurableStack, Op, OverflowPolicy, Stack, WalError};
+
+mod common;
+
+fn open<T: rust::Codec + Clone>(dir: &PathBuf) -> Result<DurableStack<T>, WalError> {
+    DurableStack::open(dir, 8, OverflowPolicy::Reject, WalOptions::default())
+}
+
+fn contents(durable: &DurableStack<i32>) -> Vec<i32> {
+    durable.stack().iter_bottom_up().copied().collect()
+}
+
+#[test]
+fn reopening_replays_the_log() {
+    let dir = common::temp_dir("wal-replay");
+    let mut durable = open(&dir).unwrap();
+    for number in 1..=3 {
+        durable.push(number).unwrap();
+    }
+    durable.pop().unwrap();
+    drop(durable);
+
+    let durable = open(&dir).unwrap();
+    assert_eq!(contents(&durable), [1, 2]);
+    assert_eq!(durable.wal().len(), 4);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_torn_record_is_cut_off() {
+    let dir = common::temp_dir("wal-torn");
+    let mut durable = open(&dir).unwrap();
+    for number in 1..=3 {
+        durable.push(number).unwrap();
+    }
+    drop(durable);
+
+    let log = dir.join(LOG_FILE);
+    let len = fs::metadata(&log).unwrap().len();
+    let file = OpenOptions::new().write(true).open(&log).unwrap();
+    file.set_len(len - 2).unwrap();
+    drop(file);
+
+    let mut durable = open(&dir).unwrap();
+    assert_eq!(contents(&durable), [1, 2]);
+    assert_eq!(durable.wal().len(), 2);
+    assert!(fs::metadata(&log).unwrap().len() < len - 2);
+
+    durable.push(4).unwrap();
+    drop(durable);
+    assert_eq!(contents(&open(&dir).unwrap()), [1, 2, 4]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_log_for_another_snapshot_is_discarded() {
+    let dir = common::temp_dir("wal-base");
+    let mut durable = open(&dir).unwrap();
+    durable.push(1).unwrap();
+    durable.push(2).unwrap();
+    drop(durable);
+
+    // As if a compaction crashed after writing the snapshot but before
+    // emptying the log.
+    let mut newer = Stack::with_capacity(8);
+    newer.push(9).unwrap();
+    snapshot::save(&newer, dir.join(SNAPSHOT_FILE), snapshot::Format::Binary).unwrap();
+
+    let durable = open(&dir).unwrap();
+    assert_eq!(contents(&durable), [9]);
+    assert!(durable.wal().is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn an_open_transaction_is_rolled_back() {
+    let dir = common::temp_dir("wal-transaction");
+    let mut durable = open(&dir).unwrap();
+    durable.push(1).unwrap();
+    durable.apply(Op::Begin).unwrap();
+    durable.push(2).unwrap();
+    durable.pop().unwrap();
+    durable.pop().unwrap();
+    drop(durable);
+
+    let durable = open(&dir).unwrap();
+    assert_eq!(contents(&durable), [1]);
+    assert!(!durable.stack().in_transaction());
+    assert_eq!(durable.wal().len(), 6);
+    drop(durable);
+
+    let durable = open(&dir).unwrap();
+    assert_eq!(contents(&durable), [1]);
+    assert!(!durable.stack().in_transaction());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_log_of_another_type_is_refused() {
+    let numbers = common::temp_dir("wal-numbers");
+    let mut durable = open(&numbers).unwrap();
+    durable.push(1).unwrap();
+    drop(durable);
+
+    let strings = common::temp_dir("wal-strings");
+    drop(open::<String>(&strings).unwrap());
+    fs::copy(numbers.join(LOG_FILE), strings.join(LOG_FILE)).unwrap();
+
+    assert!(matches!(
+        open::<String>(&strings),
+        Err(WalError::Corrupt(_))
+    ));
+    fs::remove_dir_all(&numbers).unwrap();
+    fs::remove_dir_all(&strings).unwrap();
+}
+
+/// Makes writing `file` in `dir` fail by putting a directory where its
+/// temporary copy goes.
+fn block(dir: &Path, file: &str) -> PathBuf {
+    let blocker = dir.join(format!("{}.tmp", file));
+    fs::create_dir(&blocker).unwrap();
+    blocker
+}
+
+fn compacting(dir: &Path) -> DurableStack<i32> {
+    let options = WalOptions {
+        compact_after: Some(2),
+        ..WalOptions::default()
+    };
+    DurableStack::open(dir, 8, OverflowPolicy::Reject, options).unwrap()
+}
+
+#[test]
+fn a_failed_compaction_does_not_fail_the_op() {
+    let dir = common::temp_dir("wal-compact-snapshot");
+    let mut durable = compacting(&dir);
+    let blocker = block(&dir, SNAPSHOT_FILE);
+
+    durable.push(1).unwrap();
+    assert_eq!(durable.push(2).unwrap(), None);
+    assert!(durable.take_compaction_error().is_some());
+    assert!(durable.take_compaction_error().is_none());
+    assert_eq!(contents(&durable), [1, 2]);
+    assert_eq!(durable.wal().len(), 2);
+
+    fs::remove_dir(&blocker).unwrap();
+    durable.push(3).unwrap();
+    assert!(durable.take_compaction_error().is_none());
+    assert!(durable.wal().is_empty());
+    drop(durable);
+
+    assert_eq!(contents(&open(&dir).unwrap()), [1, 2, 3]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_log_left_behind_by_a_compaction_is_reset_before_appending() {
+    let dir = common::temp_dir("wal-compact-log");
+    let mut durable = compacting(&dir);
+    let blocker = block(&dir, LOG_FILE);
+
+    durable.push(1).unwrap();
+    durable.push(2).unwrap();
+    assert!(durable.take_compaction_error().is_some());
+
+    // The snapshot already holds both elements, so nothing can be logged
+    // until the log is reset to follow it.
+    assert!(durable.push(3).is_err());
+    assert_eq!(contents(&durable), [1, 2]);
+
+    fs::remove_dir(&blocker).unwrap();
+    durable.push(3).unwrap();
+    assert_eq!(durable.wal().len(), 1);
+    drop(durable);
+
+    assert_eq!(contents(&open(&dir).unwrap()), [1, 2, 3]);
+    fs::remove_dir_all(&dir).unwrap();
+}